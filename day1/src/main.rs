@@ -1,26 +1,35 @@
-use std::collections::{HashMap, hash_map};
+use std::collections::HashMap;
 
-use tools::Opt;
+use tools::{input, trace, Error, Solution};
 
-fn main() {
-    let opt = Opt::load();
+fn main() -> tools::Result<()> {
+    tools::run::<Day1>(&tools::Opt::load())
+}
+
+struct Day1<'a> {
+    lines: Vec<&'a str>,
+}
+
+impl<'a> Solution<'a> for Day1<'a> {
+    type Part1 = u32;
+    type Part2 = u32;
 
-    let input = opt.input();
-    let lines = input.lines();
+    fn parse(input: &'a str) -> tools::Result<Self> {
+        let lines = input::lines(input).collect();
+        Ok(Self { lines })
+    }
 
-    if opt.is_part1 {
-        let total: u32 = lines
-            .map(|line| {
-                let digits: Vec<u32> = line.chars().filter_map(|c| c.to_digit(10)).collect();
-                let cal_value = digits.first().unwrap() * 10 + digits.last().unwrap();
-                cal_value
-            })
-            .sum();
+    fn part1(&self) -> tools::Result<u32> {
+        let mut total = 0;
+        for (i, line) in self.lines.iter().enumerate() {
+            let digits: Vec<u32> = line.chars().filter_map(|c| c.to_digit(10)).collect();
+            total += calibration_value(&digits).map_err(|e| e.at_line(i + 1))?;
+        }
 
-        println!("Total = {}", total);
+        Ok(total)
     }
-    // part 2
-    else {
+
+    fn part2(&self) -> tools::Result<u32> {
         let num_dict = HashMap::from([
             ("one", 1),
             ("two", 2),
@@ -33,17 +42,15 @@ fn main() {
             ("nine", 9),
         ]);
 
-        let num_words: Vec<&&str> = num_dict.keys().collect();
-
         let mut total = 0;
-        for line in lines {
+        for (n, line) in self.lines.iter().enumerate() {
             let mut digits: Vec<u32> = vec![];
             for (i, c) in line.char_indices() {
                 let remainder_str = &line[i..];
                 for (k, v) in &num_dict {
                     if remainder_str.starts_with(k) {
                         digits.push(*v);
-                        println!("Found word digit: '{}' -> {}", remainder_str, v);
+                        trace!("Found word digit: '{}' -> {}", remainder_str, v);
                     }
                 }
                 if let Some(digit) = c.to_digit(10) {
@@ -51,13 +58,30 @@ fn main() {
                 }
             }
 
-            let line_cal_value = digits.first().unwrap() * 10 + digits.last().unwrap();
-            total += line_cal_value;
+            total += calibration_value(&digits).map_err(|e| e.at_line(n + 1))?;
         }
 
-        println!("Total = {}", total);
-
+        Ok(total)
+    }
+}
 
+/// Combines the first and last digit of a line into its calibration value.
+fn calibration_value(digits: &[u32]) -> tools::Result<u32> {
+    match (digits.first(), digits.last()) {
+        (Some(first), Some(last)) => Ok(first * 10 + last),
+        _ => Err(Error::validation("line has no digits")),
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
+    #[test]
+    pub fn example() {
+        let day = Day1::parse("1abc2\npqr3stu8vwx\na1b2c3d4e5f\ntreb7uchet\n").unwrap();
+        assert_eq!(day.part1().unwrap(), 142);
+        let day = Day1::parse(include_str!("../example.txt")).unwrap();
+        assert_eq!(day.part2().unwrap(), 281);
+    }
 }
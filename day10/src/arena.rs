@@ -1,6 +1,7 @@
 use super::*;
 use std::collections::{HashMap, HashSet};
 
+#[derive(Clone)]
 pub struct Arena {
     pub pipe_map: HashMap<Position, Pipe>,
     pub ground_map: HashSet<Position>,
@@ -35,7 +36,7 @@ impl Arena {
         let max_width = lines[0].len();
         let max_height = lines.len();
 
-        println!("Start Position = {:?}", start_pos);
+        debug!("Start Position = {:?}", start_pos);
 
         let mut arena = Arena {
             pipe_map,
@@ -213,7 +214,7 @@ impl Arena {
             output.push_str("\r\n");
         }
 
-        println!("{}", output);
+        debug!("{}", output);
     }
 
     pub fn print_selection<F>(&self, predicate: F)
@@ -240,7 +241,7 @@ impl Arena {
             output.push_str("\r\n");
         }
 
-        println!("{}", output);
+        debug!("{}", output);
     }
 }
 
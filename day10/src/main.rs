@@ -4,32 +4,51 @@ use std::{
 };
 
 use itertools::Itertools;
-use tools::Opt;
+use tools::{debug, input, trace, Error, Solution};
 
 use crate::walker::PipeWalker;
 
 mod arena;
 mod walker;
 
-fn main() {
-    let opt = Opt::load();
-    let input = opt.input();
-    let lines: Vec<&str> = input.lines().collect();
-    let width = lines[0].len();
-    let height = lines.len();
+fn main() -> tools::Result<()> {
+    tools::run::<Day10>(&tools::Opt::load())
+}
+
+struct Day10 {
+    arena: arena::Arena,
+}
 
-    let arena = arena::Arena::new(lines);
-    // arena.print();
-    println!("Found {} tiles.", arena.pipe_map.len());
+impl Solution<'_> for Day10 {
+    type Part1 = usize;
+    type Part2 = usize;
 
-    let mut walker = PipeWalker::new(arena);
+    fn parse(input: &str) -> tools::Result<Self> {
+        let lines: Vec<&str> = input::lines(input).collect();
+        if lines.is_empty() {
+            return Err(Error::validation("input is empty"));
+        }
 
-    while let Some((score, pos)) = walker.next() {
-        // println!("[{score}] = {pos}");
+        let arena = arena::Arena::new(lines);
+        // arena.print();
+        debug!("Found {} tiles.", arena.pipe_map.len());
+
+        Ok(Self { arena })
     }
 
-    walker.arena.print_selection(|p| walker.arena.ground_map.contains(p) || walker.score_map.values().contains(p));
+    fn part1(&self) -> tools::Result<usize> {
+        let mut walker = PipeWalker::new(self.arena.clone());
 
-    println!("Max distance = {}", walker.score_map.len() / 2);
-}
+        while let Some((score, pos)) = walker.next() {
+            trace!("[{score}] = {pos}");
+        }
 
+        walker.arena.print_selection(|p| walker.arena.ground_map.contains(p) || walker.score_map.values().contains(p));
+
+        Ok(walker.score_map.len() / 2)
+    }
+
+    fn part2(&self) -> tools::Result<usize> {
+        Err(Error::Unsolved)
+    }
+}
@@ -1,106 +1,135 @@
-use std::{
-    fs::File,
-    io::{BufRead, BufReader},
-};
-
 use itertools::Itertools;
-use tools::Opt;
-
-
-fn main() {
-    let opt = Opt::load();
-    println!("Options: file={:?}, part1={}", opt.file(), opt.is_part1);
-
-    let input = File::open(&opt.file()).unwrap();
-    let reader = BufReader::new(input);
-    let input_lines: Vec<String> = reader.lines().filter_map(|f| f.ok()).collect();
-    let max_x = input_lines.first().unwrap().len();
-    let max_y = input_lines.len();
-    println!("Max X = {max_x}, Max Y = {max_y}");
-
-    let mut galaxies = vec![];
-    let mut curr_id: u32 = 1;
-    for (y, line) in input_lines.iter().enumerate() {
-        for (x, c) in line.chars().enumerate() {
-            if c == '#' {
-                let galaxy = Galaxy {
-                    id: Id(curr_id),
-                    position: Position { x, y },
-                };
-                galaxies.push(galaxy);
-                curr_id += 1;
-            }
-        }
-    }
+use tools::{debug, input, trace, Error, Solution};
+
+fn main() -> tools::Result<()> {
+    tools::run::<Day11>(&tools::Opt::load())
+}
+
+struct Day11 {
+    galaxies: Vec<Galaxy>,
+    empty_cols: Vec<usize>,
+    empty_rows: Vec<usize>,
+}
 
-    // println!("Start Galaxies = {:#?}", galaxies);
+impl Solution<'_> for Day11 {
+    type Part1 = usize;
+    type Part2 = usize;
 
-    let mut empty_cols = vec![];
-    for x in 0..max_x {
-        if !galaxies.iter().any(|g| g.position.x == x) {
-            empty_cols.push(x);
+    fn parse(input: &str) -> tools::Result<Self> {
+        let input_lines: Vec<&str> = input::lines(input).collect();
+        let max_x = input_lines
+            .first()
+            .ok_or_else(|| Error::validation("input is empty"))?
+            .len();
+        let max_y = input_lines.len();
+        debug!("Max X = {max_x}, Max Y = {max_y}");
+
+        let mut galaxies = vec![];
+        let mut curr_id: u32 = 1;
+        for (y, line) in input_lines.iter().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                if c == '#' {
+                    let galaxy = Galaxy {
+                        id: Id(curr_id),
+                        position: Position { x, y },
+                    };
+                    galaxies.push(galaxy);
+                    curr_id += 1;
+                }
+            }
         }
-    }
 
-    let mut empty_rows = vec![];
-    for y in 0..max_y {
-        if !galaxies.iter().any(|g| g.position.y == y) {
-            empty_rows.push(y);
+        let mut empty_cols = vec![];
+        for x in 0..max_x {
+            if !galaxies.iter().any(|g| g.position.x == x) {
+                empty_cols.push(x);
+            }
         }
-    }
 
-    println!("Empty X = {empty_cols:#?}");
-    println!("Empty Y = {empty_rows:#?}");
+        let mut empty_rows = vec![];
+        for y in 0..max_y {
+            if !galaxies.iter().any(|g| g.position.y == y) {
+                empty_rows.push(y);
+            }
+        }
 
-    // Expand space
+        debug!("Empty X = {empty_cols:#?}");
+        debug!("Empty Y = {empty_rows:#?}");
 
-    let expansion_amt = 999_999;
-    for galaxy in &mut galaxies {
-        let empty_cols_before = empty_cols
-            .iter()
-            .filter(|x| galaxy.position.x > **x)
-            .collect::<Vec<_>>()
-            .len();
-        let empty_rows_before = empty_rows
-            .iter()
-            .filter(|y| galaxy.position.y > **y)
-            .collect::<Vec<_>>()
-            .len();
+        Ok(Self {
+            galaxies,
+            empty_cols,
+            empty_rows,
+        })
+    }
 
-        let x_expansion = if empty_cols_before > 0 {
-            empty_cols_before  * expansion_amt
-        } else { 0 };
-        let y_expansion = if empty_rows_before > 0 {
-            empty_rows_before * expansion_amt
-        } else { 0 };
-
-        galaxy
-            .position
-            .expand_x(x_expansion);
-        galaxy
-            .position
-            .expand_y(y_expansion);
+    /// Empty rows and columns are twice as big.
+    fn part1(&self) -> tools::Result<usize> {
+        Ok(self.total_distance(1))
     }
 
-    let combos: Vec<(&Galaxy, &Galaxy)> = galaxies.iter().tuple_combinations().collect();
-    println!("Number of galaxy combos = {}", combos.len());
-    let mut total_distance = 0;
-    for (a, b) in &combos {
-        if a.id.0 == 5 && b.id.0 == 9 {
-            // println!("#5: {:?}, #9: {:?}", a, b);
+    /// Empty rows and columns are a million times as big, or the `expansion` param.
+    fn part2(&self) -> tools::Result<usize> {
+        let expansion: usize = tools::param_or("expansion", 1_000_000)?;
+        if expansion == 0 {
+            return Err(Error::validation("expansion must be at least 1"));
         }
-        let distance = a.distance(b);
-        total_distance += distance;
-        // println!("({:?},{:?}) = {}", a.id.0, b.id.0, distance);
+        Ok(self.total_distance(expansion - 1))
     }
+}
+
+impl Day11 {
+    /// Expands every empty row and column by `expansion_amt`, and sums the distances between all galaxy pairs.
+    fn total_distance(&self, expansion_amt: usize) -> usize {
+        let mut galaxies = self.galaxies.clone();
+        let empty_cols = &self.empty_cols;
+        let empty_rows = &self.empty_rows;
+
+        // Expand space
+        for galaxy in &mut galaxies {
+            let empty_cols_before = empty_cols
+                .iter()
+                .filter(|x| galaxy.position.x > **x)
+                .collect::<Vec<_>>()
+                .len();
+            let empty_rows_before = empty_rows
+                .iter()
+                .filter(|y| galaxy.position.y > **y)
+                .collect::<Vec<_>>()
+                .len();
+
+            let x_expansion = if empty_cols_before > 0 {
+                empty_cols_before  * expansion_amt
+            } else { 0 };
+            let y_expansion = if empty_rows_before > 0 {
+                empty_rows_before * expansion_amt
+            } else { 0 };
+
+            galaxy
+                .position
+                .expand_x(x_expansion);
+            galaxy
+                .position
+                .expand_y(y_expansion);
+        }
 
-    println!("Total Distance = {total_distance}");
+        let combos: Vec<(&Galaxy, &Galaxy)> = galaxies.iter().tuple_combinations().collect();
+        debug!("Number of galaxy combos = {}", combos.len());
+        let mut total_distance = 0;
+        for (a, b) in &combos {
+            let distance = a.distance(b);
+            trace!("({},{}) = {distance}", a.id.0, b.id.0);
+            total_distance += distance;
+        }
+
+        total_distance
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Id(u32);
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Galaxy {
     id: Id,
     position: Position,
@@ -112,7 +141,7 @@ impl Galaxy {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Position {
     x: usize,
     y: usize,
@@ -137,3 +166,17 @@ impl Position {
         self.y += amt;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn example() {
+        let day = Day11::parse(include_str!("../example.txt")).unwrap();
+        assert_eq!(day.part1().unwrap(), 374);
+        // 10 and 100 times as big, as given for part 2
+        assert_eq!(day.total_distance(9), 1030);
+        assert_eq!(day.total_distance(99), 8410);
+    }
+}
@@ -2,23 +2,44 @@ use std::fmt::{Debug, Display};
 
 use intbits::Bits;
 use itertools::Itertools;
-use tools::Opt;
+use tools::{input, Error, Solution};
 
-fn main() {
-    let opt = Opt::load();
-    let input = opt.input();
-    let lines: Vec<&str> = input.lines().collect();
+fn main() -> tools::Result<()> {
+    tools::run::<Day12>(&tools::Opt::load())
+}
+
+struct Day12 {
+    rows: Vec<Row>,
+}
 
-    let rows: Vec<Row> = lines.iter().map(|l| Row::new(l)).collect();
+impl Solution<'_> for Day12 {
+    type Part1 = usize;
+    type Part2 = usize;
 
-    let mut cnt = 0;
+    fn parse(input: &str) -> tools::Result<Self> {
+        let rows: Vec<Row> = input::lines(input)
+            .enumerate()
+            .map(|(i, l)| Row::new(l).map_err(|e| e.at_line(i + 1)))
+            .collect::<tools::Result<_>>()?;
+        Ok(Self { rows })
+    }
 
-    for row in rows {
-        let a = row.get_arrangements_bruteforce();
-        cnt += a.len();
+    fn part1(&self) -> tools::Result<usize> {
+        Ok(self.rows.iter().map(|row| row.get_arrangements_bruteforce().len()).sum())
     }
 
-    println!("Total count = {cnt}");
+    /// With `--shard`, only counts this process's share of the rows.
+    fn part2(&self) -> tools::Result<usize> {
+        let times = tools::param_or("unfold", 5)?;
+        let mut cnt = 0;
+
+        for row in tools::shard::split(&self.rows) {
+            let a = row.unfold(times).get_arrangements_bruteforce();
+            cnt += a.len();
+        }
+
+        Ok(cnt)
+    }
 }
 
 struct Row {
@@ -27,17 +48,36 @@ struct Row {
 }
 
 impl Row {
-    pub fn new(line: &str) -> Self {
-        let parts: Vec<&str> = line.split(' ').collect();
-        let mut springs: Vec<Spring> = parts[0].chars().map(|c| Spring::from_char(c)).collect();
-        let groups: Vec<usize> = parts[1]
+    pub fn new(line: &str) -> tools::Result<Self> {
+        let Some((springs_txt, groups_txt)) = line.split_once(' ') else {
+            return Err(Error::parse_in(line, line, "expected `<springs> <groups>`"));
+        };
+        let springs: Vec<Spring> = springs_txt
+            .char_indices()
+            .map(|(i, c)| {
+                Spring::try_from(c).map_err(|e| {
+                    let spring = &springs_txt[i..i + c.len_utf8()];
+                    Error::parse_in(line, spring, e.to_string())
+                })
+            })
+            .collect::<tools::Result<_>>()?;
+        let groups: Vec<usize> = groups_txt
             .split(',')
-            .map(|s| s.parse::<usize>().unwrap())
-            .collect();
+            .map(|s| {
+                s.parse::<usize>()
+                    .map_err(|e| Error::parse_in(line, s, format!("invalid group size: {e}")))
+            })
+            .collect::<tools::Result<_>>()?;
+
+        Ok(Row { springs, groups })
+    }
 
+    /// Repeats the row `times` times, with unknown springs in between copies.
+    pub fn unfold(&self, times: usize) -> Row {
+        let mut springs = self.springs.clone();
         springs.push(Spring::Unknown);
-        let unfolded_springs = (0..5).map(|_| springs.clone()).concat();
-        let unfolded_groups = (0..5).map(|_| groups.clone()).concat();
+        let unfolded_springs = (0..times).map(|_| springs.clone()).concat();
+        let unfolded_groups = (0..times).map(|_| self.groups.clone()).concat();
 
         Row {
             springs: unfolded_springs,
@@ -72,7 +112,6 @@ impl Row {
         let known_springs = [Spring::Good, Spring::Bad];
         let mut good_spring_arrangements = vec![];
 
-        // println!("{:?} => {:?}", self.springs, self.groups);
         let number_of_unknowns = self
             .springs
             .iter()
@@ -86,8 +125,6 @@ impl Row {
             .positions(|s| *s == Spring::Unknown)
             .collect();
 
-        // println!("Unk pos = {:?}", unk_positions);
-        // println!("Perms ({})= {:?}", perms.len(), perms);
 
         for perm in &perms {
             // Create temporary springs replaced with permuatation
@@ -100,12 +137,10 @@ impl Row {
             // Test new springs to see if fits groupings
             let working_groups = Self::get_groups(&working_springs);
             if working_groups == self.groups {
-                // println!("found perm: {working_springs:?} => {:?}", working_groups);
                 good_spring_arrangements.push(working_springs);
             }
         }
 
-        // println!("");
         good_spring_arrangements
     }
 
@@ -133,16 +168,32 @@ impl Display for Spring {
     }
 }
 
-impl Spring {
-    pub fn from_char(c: char) -> Self {
+/// A character other than `.`, `#` or `?`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct InvalidSpring(char);
+
+impl Display for InvalidSpring {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid spring {:?}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidSpring {}
+
+impl TryFrom<char> for Spring {
+    type Error = InvalidSpring;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
         match c {
-            '.' => Spring::Good,
-            '#' => Spring::Bad,
-            '?' => Spring::Unknown,
-            _ => panic!("Invalid spring character: {c}"),
+            '.' => Ok(Spring::Good),
+            '#' => Ok(Spring::Bad),
+            '?' => Ok(Spring::Unknown),
+            _ => Err(InvalidSpring(c)),
         }
     }
+}
 
+impl Spring {
     pub fn to_char(&self) -> char {
         match self {
             Spring::Good => '.',
@@ -165,3 +216,22 @@ fn get_spring_combos(k: usize) -> Vec<Vec<Spring>> {
 
     combos
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn springs() {
+        for c in ['.', '#', '?'] {
+            assert_eq!(Spring::try_from(c).unwrap().to_char(), c);
+        }
+        assert_eq!(Spring::try_from('x'), Err(InvalidSpring('x')));
+
+        let e = Row::new("#.x 1,1").err().unwrap().to_string();
+        assert!(
+            e.starts_with("<input>:1:3: parse error: invalid spring 'x'"),
+            "{e}"
+        );
+    }
+}
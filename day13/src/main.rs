@@ -1,100 +1,126 @@
 use std::collections::HashMap;
 
-use tools::Opt;
-
-fn main() {
-    let opt = Opt::load();
-    let input = opt.input();
-
-    let mut sections: Vec<Section> = input
-        .split("\r\n\r\n")
-        .map(|section| {
-            section
-                .lines()
-                .map(|l| l.chars().map(|c| c == '#').collect())
-                .collect()
-        })
-        .map(|v| Section::new(v))
-        .collect();
-
-    let mut total_vert = 0;
-    let mut total_horz = 0;
-
-    let mut pre_valid_results = HashMap::new();
-
-    for (i, section) in sections.iter().enumerate() {
-        println!("Section #{i}: ");
-        for sus in section.scan_vertical() {
-            if section.verify_vertical(sus) {
-                println!("\tV = {sus}");
-                pre_valid_results.insert(
-                    i,
-                    PreValidResult {
-                        mirror_idx: sus,
-                        is_vertical: true,
-                    },
-                );
-                total_vert += sus;
-            }
-        }
-        for sus in section.scan_horizontal() {
-            if section.verify_horizontal(sus) {
-                pre_valid_results.insert(
-                    i,
-                    PreValidResult {
-                        mirror_idx: sus,
-                        is_vertical: false,
-                    },
-                );
-                println!("\tH = {sus}");
-                total_horz += sus;
-            }
-        }
+use tools::{debug, input, Error, Solution};
+
+fn main() -> tools::Result<()> {
+    tools::run::<Day13>(&tools::Opt::load())
+}
+
+struct Day13 {
+    sections: Vec<Section>,
+}
+
+impl Solution<'_> for Day13 {
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> tools::Result<Self> {
+        let sections: Vec<Section> = input::blocks(input)
+            .map(|section| {
+                input::lines(section)
+                    .map(|l| l.chars().map(|c| c == '#').collect())
+                    .collect()
+            })
+            .map(|v| Section::new(v))
+            .collect();
+
+        Ok(Self { sections })
     }
 
-    total_vert = 0;
-    total_horz = 0;
+    fn part1(&self) -> tools::Result<usize> {
+        let (_, score) = self.find_mirrors();
+        Ok(score)
+    }
 
-    'sections: for (i, section) in sections.iter_mut().enumerate() {
-        println!("Section #{i}: ");
-        let pre_result = pre_valid_results.get(&i).unwrap();
-        for row in 0..section.height() {
-            for col in 0..section.width() {
-                if i == 1 && row == 6 && col == 10 {
-                    println!("DEBUG");
-                }
-                let mut bit = section.tiles[row][col];
-                bit = !bit;
-                section.tiles[row][col] = bit;
-
-                for sus in section.scan_vertical() {
-                    if section.verify_vertical(sus) {
-                        if pre_result.mirror_idx != sus || !pre_result.is_vertical {
-                            println!("\tV = {sus}");
-                            total_vert += sus;
-                            continue 'sections;
+    /// Each section has exactly one smudge, which changes its mirror line.
+    fn part2(&self) -> tools::Result<usize> {
+        let (pre_valid_results, _) = self.find_mirrors();
+        let mut sections = self.sections.clone();
+
+        let mut total_vert = 0;
+        let mut total_horz = 0;
+
+        'sections: for (i, section) in sections.iter_mut().enumerate() {
+            debug!("Section #{i}: ");
+            let pre_result = pre_valid_results
+                .get(&i)
+                .ok_or_else(|| Error::validation(format!("section #{i} has no mirror line")))?;
+            for row in 0..section.height() {
+                for col in 0..section.width() {
+                    let mut bit = section.tiles[row][col];
+                    bit = !bit;
+                    section.tiles[row][col] = bit;
+
+                    for sus in section.scan_vertical() {
+                        if section.verify_vertical(sus) {
+                            if pre_result.mirror_idx != sus || !pre_result.is_vertical {
+                                debug!("\tV = {sus}");
+                                total_vert += sus;
+                                continue 'sections;
+                            }
                         }
                     }
-                }
-                for sus in section.scan_horizontal() {
-                    if section.verify_horizontal(sus) {
-                        if pre_result.mirror_idx != sus || pre_result.is_vertical {
-                            println!("\tH = {sus}");
-                            total_horz += sus;
-                            continue 'sections;
+                    for sus in section.scan_horizontal() {
+                        if section.verify_horizontal(sus) {
+                            if pre_result.mirror_idx != sus || pre_result.is_vertical {
+                                debug!("\tH = {sus}");
+                                total_horz += sus;
+                                continue 'sections;
+                            }
                         }
                     }
-                }
 
-                section.tiles[row][col] = !bit;
+                    section.tiles[row][col] = !bit;
+                }
             }
         }
+
+        debug!("Total H = {total_horz}, Total V = {total_vert}");
+        Ok(total_horz * 100 + total_vert)
     }
+}
+
+impl Day13 {
+    /// Finds the mirror line of each section, and the summarized score of all of them.
+    fn find_mirrors(&self) -> (HashMap<usize, PreValidResult>, usize) {
+        let mut total_vert = 0;
+        let mut total_horz = 0;
 
-    println!(
-        "Total H = {total_horz}, Total V = {total_vert} ==> SCORE = {}",
-        total_horz * 100 + total_vert
-    );
+        let mut pre_valid_results = HashMap::new();
+
+        for (i, section) in self.sections.iter().enumerate() {
+            debug!("Section #{i}: ");
+            for sus in section.scan_vertical() {
+                if section.verify_vertical(sus) {
+                    debug!("\tV = {sus}");
+                    pre_valid_results.insert(
+                        i,
+                        PreValidResult {
+                            mirror_idx: sus,
+                            is_vertical: true,
+                        },
+                    );
+                    total_vert += sus;
+                }
+            }
+            for sus in section.scan_horizontal() {
+                if section.verify_horizontal(sus) {
+                    pre_valid_results.insert(
+                        i,
+                        PreValidResult {
+                            mirror_idx: sus,
+                            is_vertical: false,
+                        },
+                    );
+                    debug!("\tH = {sus}");
+                    total_horz += sus;
+                }
+            }
+        }
+
+        debug!("Total H = {total_horz}, Total V = {total_vert}");
+        (pre_valid_results, total_horz * 100 + total_vert)
+    }
 }
 
 struct PreValidResult {
@@ -102,6 +128,7 @@ struct PreValidResult {
     is_vertical: bool,
 }
 
+#[derive(Clone)]
 struct Section {
     /// Outer Vec is rows, inner Vec is columns
     tiles: Vec<Vec<bool>>,
@@ -223,3 +250,15 @@ impl Section {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn example() {
+        let day = Day13::parse(include_str!("../example.txt")).unwrap();
+        assert_eq!(day.part1().unwrap(), 405);
+        assert_eq!(day.part2().unwrap(), 400);
+    }
+}
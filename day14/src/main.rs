@@ -1,62 +1,91 @@
-use std::{collections::HashMap, ops::Range, time::Instant, fmt::Display};
+use std::{collections::HashMap, ops::Range, fmt::Display};
 
-use tools::Opt;
+use tools::progress::Progress;
+use tools::input::ByteGrid;
+use tools::{debug, trace, Error, Solution};
 
 const BALL: u8 = 0u8;
 const CUBE: u8 = 0xffu8;
 const EMPTY: u8 = 1u8;
 
-fn main() {
-    let opt = Opt::load();
-    let input = opt.input();
-    let lines: Vec<Vec<u8>> = input
-        .lines()
-        .map(|l| {
-            l.bytes()
-                .map(|b| match b {
-                    b'.' => 1u8,
-                    b'O' => 0u8,
-                    b'#' => 0xffu8,
-                    _ => panic!("invalid char"),
-                })
-                .collect()
-        })
-        .collect();
-
-    let height = lines.len();
-    let width = lines[0].len();
-
-    let mut arena = Arena::new(lines);
-
-    let start = Instant::now();
-    let mut loads = vec![];
-    let directions_cycle = vec![
-        Direction::North,
-        Direction::West,
-        Direction::South,
-        Direction::East,
-    ];
-    for n in 0..250usize {
-        for (i, dir) in directions_cycle.iter().enumerate() {
-            // let start2 = Instant::now();
-            arena.tilt(*dir);
-            // let elapsed2 = Instant::now() - start2;
-            // println!("Cycle {elapsed2:?}");
+fn main() -> tools::Result<()> {
+    tools::run::<Day14>(&tools::Opt::load())
+}
+
+struct Day14<'a> {
+    grid: ByteGrid<'a>,
+}
+
+impl<'a> Solution<'a> for Day14<'a> {
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &'a str) -> tools::Result<Self> {
+        let grid = ByteGrid::new(input)?;
+        grid.check(|b| tile(b).is_some())?;
+
+        // Tilting works by rotating the arena, so it has to be square
+        if grid.width() != grid.height() {
+            return Err(Error::validation(format!(
+                "arena is {}x{}, expected a square",
+                grid.width(),
+                grid.height()
+            )));
+        }
+
+        Ok(Self { grid })
+    }
+
+    fn part1(&self) -> tools::Result<usize> {
+        let mut arena = Arena::from_grid(&self.grid);
+        arena.tilt(Direction::North);
+
+        Ok(arena.calc_north_load())
+    }
+
+    /// Spins the arena a billion times, or the `cycles` param. The arena settles into a loop,
+    /// so only spin until a previous state repeats, and skip the rest of the loops.
+    fn part2(&self) -> tools::Result<usize> {
+        let cycles: usize = tools::param_or("cycles", 1_000_000_000)?;
+        self.load_after(cycles)
+    }
+}
+
+impl Day14<'_> {
+    /// Gets the north load after spinning `cycles` times.
+    fn load_after(&self, cycles: usize) -> tools::Result<usize> {
+        let mut arena = Arena::from_grid(&self.grid);
+
+        // Load after n cycles, and the first cycle each state was seen at
+        let mut loads = vec![];
+        let mut seen: HashMap<Vec<Vec<u8>>, usize> = HashMap::new();
+        let directions_cycle = vec![
+            Direction::North,
+            Direction::West,
+            Direction::South,
+            Direction::East,
+        ];
+        let mut progress = Progress::with_total("cycles", cycles);
+        for n in 0..cycles {
+            if let Some(first) = seen.insert(arena.data.clone(), n) {
+                let period = n - first;
+                debug!("Cycle {first} repeats every {period} cycles");
+
+                return Ok(loads[first + (cycles - first) % period]);
+            }
+
+            progress.tick();
+            progress.check()?;
             let load = arena.calc_north_load();
-            println!("[{}] ({dir}) Load = {}", n + i, load);
-            if n > (250 - 100) {
-                loads.push(load);
+            trace!("[{n}] Load = {load}");
+            loads.push(load);
+
+            for dir in &directions_cycle {
+                arena.tilt(*dir);
             }
         }
-    }
-    let elapsed = Instant::now() - start;
-    println!("All {elapsed:?}");
 
-    let quarters: Vec<Vec<usize>> = (0..4)
-        .map(|n| loads.iter().skip(n).step_by(4).cloned().collect())
-        .collect();
-    for q in quarters {
-        println!("{q:?}");
+        Ok(arena.calc_north_load())
     }
 }
 
@@ -71,6 +100,15 @@ impl Arena {
         Self { data, size }
     }
 
+    /// Builds the arena from a grid already checked with [`tile`].
+    pub fn from_grid(grid: &ByteGrid) -> Self {
+        let data = grid
+            .rows()
+            .map(|row| row.iter().map(|b| tile(*b).expect("checked when parsed")).collect())
+            .collect();
+        Self::new(data)
+    }
+
     /// Rotates all data counter-clockwise (North becomes West).
     pub fn rotate_ccw(&mut self) {
         let mut new_data = vec![vec![0u8; self.size]; self.size];
@@ -141,6 +179,16 @@ impl Arena {
     }
 }
 
+/// Gets the tile for an input byte, or `None` if it isn't one.
+fn tile(b: u8) -> Option<u8> {
+    match b {
+        b'.' => Some(EMPTY),
+        b'O' => Some(BALL),
+        b'#' => Some(CUBE),
+        _ => None,
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 enum Direction {
     West = 0,
@@ -216,6 +264,27 @@ where
 mod test {
     use super::*;
 
+    #[test]
+    pub fn example() {
+        let day = Day14::parse(include_str!("../example.txt")).unwrap();
+        assert_eq!(day.part1().unwrap(), 136);
+        assert_eq!(day.part2().unwrap(), 64);
+
+        // Skipping repeated loops gives the same load as spinning every cycle
+        let mut arena = Arena::from_grid(&day.grid);
+        for cycles in 0..30 {
+            assert_eq!(day.load_after(cycles).unwrap(), arena.calc_north_load());
+            for dir in [
+                Direction::North,
+                Direction::West,
+                Direction::South,
+                Direction::East,
+            ] {
+                arena.tilt(dir);
+            }
+        }
+    }
+
     #[test]
     pub fn ccw() {
         let arena = Arena::new(vec![vec![0u8; 100]; 100]);
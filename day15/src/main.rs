@@ -1,55 +1,80 @@
 use std::{
     collections::HashMap,
+    fmt::Display,
     hash::{BuildHasher, Hasher},
+    num::ParseIntError,
+    str::FromStr,
 };
 
 use itertools::Itertools;
-use tools::Opt;
+use tools::{debug, Error, Solution};
 
-fn main() {
-    let opt = Opt::load();
-    let input = opt.input();
+fn main() -> tools::Result<()> {
+    tools::run::<Day15>(&tools::Opt::load())
+}
+
+struct Day15<'a> {
+    input: &'a str,
+    steps: Vec<&'a str>,
+}
+
+impl<'a> Solution<'a> for Day15<'a> {
+    type Part1 = usize;
+    type Part2 = usize;
 
-    let seq_items: Vec<&str> = input
-        .split(&[',', '\r', '\n'])
-        .filter(|s| !s.is_empty())
-        .collect();
-    let operations: Vec<Operation> = seq_items.iter().map(|s| Operation::new(s)).collect();
+    fn parse(input: &'a str) -> tools::Result<Self> {
+        let steps: Vec<&str> = input
+            .split(&[',', '\r', '\n'])
+            .filter(|s| !s.is_empty())
+            .collect();
 
-    let mut boxes: HashMap<u8, LensBox> = HashMap::new();
-    for n in 0..=255u8 {
-        boxes.insert(n, LensBox::new());
+        Ok(Self { input, steps })
     }
 
-    for op in operations {
-        let label_hash = hash(&op.label);
-        let box_entry = boxes.entry(label_hash);
-        // Add
-        if let Some(focal) = op.focal {
-            box_entry.and_modify(|b| b.add_lens(&op.label, focal));
-        }
-        // Remove
-        else {
-            box_entry.and_modify(|b| b.remove_lens(&op.label));
-        }
+    fn part1(&self) -> tools::Result<usize> {
+        Ok(self.steps.iter().map(|s| hash(s) as usize).sum())
     }
 
-    let mut total_power = 0;
-    for i in 0..=255u8 {
-        let b = &boxes[&i];
-        if b.lenses.len() > 0 {
-            let lens_str = b.lenses.iter().fold(String::new(), |mut acc, b| {
-                acc.push_str(&format!("[{} {}] ", b.label, b.focal));
-                acc
-            });
-            let box_focal_power = b.focal_power() * (i as usize + 1);
-            println!("Box {i} ({}): {}", box_focal_power,lens_str);
-            total_power += box_focal_power;
+    fn part2(&self) -> tools::Result<usize> {
+        let operations: Vec<Operation> = self
+            .steps
+            .iter()
+            .map(|s| parse_operation(self.input, s))
+            .collect::<tools::Result<_>>()?;
+
+        let mut boxes: HashMap<u8, LensBox> = HashMap::new();
+        for n in 0..=255u8 {
+            boxes.insert(n, LensBox::new());
+        }
+
+        for op in operations {
+            let box_entry = boxes.entry(hash(op.label()));
+            match &op {
+                Operation::Add { label, focal } => {
+                    box_entry.and_modify(|b| b.add_lens(label, *focal));
+                }
+                Operation::Remove { label } => {
+                    box_entry.and_modify(|b| b.remove_lens(label));
+                }
+            }
         }
-    }
 
-    println!("Total Power = {total_power}");
+        let mut total_power = 0;
+        for i in 0..=255u8 {
+            let b = &boxes[&i];
+            if b.lenses.len() > 0 {
+                let lens_str = b.lenses.iter().fold(String::new(), |mut acc, b| {
+                    acc.push_str(&format!("[{} {}] ", b.label, b.focal));
+                    acc
+                });
+                let box_focal_power = b.focal_power() * (i as usize + 1);
+                debug!("Box {i} ({}): {}", box_focal_power,lens_str);
+                total_power += box_focal_power;
+            }
+        }
 
+        Ok(total_power)
+    }
 }
 
 struct LensBox {
@@ -128,28 +153,104 @@ impl Hasher for LabelHasher {
 }
 
 #[derive(Debug)]
-struct Operation {
-    label: String,
-    focal: Option<usize>,
+enum Operation {
+    Add { label: String, focal: usize },
+    Remove { label: String },
 }
 
-impl Operation {
-    pub fn new(input: &str) -> Operation {
-        if input.contains('=') {
-            let mut parts = input.split('=');
-            Operation {
-                label: parts.next().unwrap().to_owned(),
-                focal: Some(parts.next().unwrap().parse().unwrap()),
+/// Why a step is not an operation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ParseOperationError {
+    /// Neither `=` nor a trailing `-` after the label.
+    MissingOperator,
+    /// Nothing before the operator.
+    EmptyLabel,
+    /// A character in the label other than a letter.
+    InvalidLabel(char),
+    /// The focal length after `=` is not a number.
+    InvalidFocal(ParseIntError),
+}
+
+impl Display for ParseOperationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseOperationError::MissingOperator => write!(f, "expected `=` or `-` after label"),
+            ParseOperationError::EmptyLabel => write!(f, "missing label"),
+            ParseOperationError::InvalidLabel(c) => write!(f, "invalid label character {c:?}"),
+            ParseOperationError::InvalidFocal(e) => write!(f, "invalid focal: {e}"),
+        }
+    }
+}
+
+impl ParseOperationError {
+    /// Gets the part of `step` this error is about.
+    fn span<'a>(&self, step: &'a str) -> &'a str {
+        match self {
+            ParseOperationError::MissingOperator => step,
+            ParseOperationError::EmptyLabel => &step[..0],
+            ParseOperationError::InvalidLabel(c) => {
+                let i = step.find(*c).unwrap_or(0);
+                &step[i..i + c.len_utf8()]
             }
-        } else {
-            Operation {
-                label: input.replace("-", ""),
-                focal: None,
+            ParseOperationError::InvalidFocal(_) => {
+                step.split_once('=').map_or(step, |(_, focal)| focal)
             }
         }
     }
 }
 
+impl std::error::Error for ParseOperationError {}
+
+/// Parses a step, a slice of `input`, with errors pointing at the part of it that is invalid.
+fn parse_operation(input: &str, step: &str) -> tools::Result<Operation> {
+    step.parse::<Operation>()
+        .map_err(|e| Error::parse_in(input, e.span(step), e.to_string()))
+}
+
+impl FromStr for Operation {
+    type Err = ParseOperationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // A `-` in the focal length of an add is then an invalid focal, not a remove
+        let (label, focal) = match (s.split_once('='), s.strip_suffix('-')) {
+            (Some((label, focal)), _) => (label, Some(focal)),
+            (None, Some(label)) => (label, None),
+            (None, None) => return Err(ParseOperationError::MissingOperator),
+        };
+        if label.is_empty() {
+            return Err(ParseOperationError::EmptyLabel);
+        }
+        if let Some(c) = label.chars().find(|c| !c.is_ascii_alphabetic()) {
+            return Err(ParseOperationError::InvalidLabel(c));
+        }
+
+        let label = label.to_owned();
+        match focal {
+            Some(focal) => Ok(Operation::Add {
+                label,
+                focal: focal.parse().map_err(ParseOperationError::InvalidFocal)?,
+            }),
+            None => Ok(Operation::Remove { label }),
+        }
+    }
+}
+
+impl TryFrom<&str> for Operation {
+    type Error = ParseOperationError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl Operation {
+    pub fn label(&self) -> &str {
+        match self {
+            Operation::Add { label, .. } | Operation::Remove { label } => label,
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -162,13 +263,59 @@ mod test {
         assert_eq!(res, 52u8);
     }
 
+    #[test]
+    pub fn example() {
+        let day = Day15::parse(include_str!("../example.txt")).unwrap();
+        assert_eq!(day.part1().unwrap(), 1320);
+        assert_eq!(day.part2().unwrap(), 145);
+    }
+
     #[test]
     pub fn example_labels() {
         let example = include_str!("../example.txt");
-        let operations: Vec<Operation> = example.split(',').map(|s| Operation::new(s)).collect();
+        let operations: Vec<Operation> = example.split(',').map(|s| s.parse().unwrap()).collect();
 
         for op in operations {
-            println!("{} => {}", op.label, hash(&op.label));
+            println!("{} => {}", op.label(), hash(op.label()));
         }
     }
+
+    #[test]
+    pub fn invalid_operations() {
+        assert!(matches!(
+            Operation::try_from("cm-"),
+            Ok(Operation::Remove { label }) if label == "cm"
+        ));
+
+        assert!(matches!(
+            "rn=x".parse::<Operation>(),
+            Err(ParseOperationError::InvalidFocal(_))
+        ));
+        assert_eq!(
+            "rn".parse::<Operation>().unwrap_err(),
+            ParseOperationError::MissingOperator
+        );
+        assert_eq!(
+            "=1".parse::<Operation>().unwrap_err(),
+            ParseOperationError::EmptyLabel
+        );
+        assert!(matches!(
+            "ab=3-".parse::<Operation>(),
+            Err(ParseOperationError::InvalidFocal(_))
+        ));
+        assert_eq!(
+            "a-=3".parse::<Operation>().unwrap_err(),
+            ParseOperationError::InvalidLabel('-')
+        );
+
+        let e = parse_operation("rn=x", "rn=x").unwrap_err().to_string();
+        assert!(e.starts_with("<input>:1:4: parse error: invalid focal"), "{e}");
+        let e = parse_operation("rn", "rn").unwrap_err().to_string();
+        assert!(e.contains("expected `=` or `-` after label"), "{e}");
+
+        // Located in the whole input, not just the step
+        let day = Day15::parse("rn=1,cm-,qp=x\n").unwrap();
+        let e = day.part2().unwrap_err().to_string();
+        assert!(e.starts_with("<input>:1:13: parse error: invalid focal"), "{e}");
+    }
 }
@@ -5,92 +5,122 @@ use std::{
 };
 
 use itertools::Itertools;
-use tools::Opt;
+use tools::progress::Progress;
+use tools::input::ByteGrid;
+use tools::{debug, trace, Solution};
 
 const SLASH: char = '\\';
 const FSLASH: char = '/';
 const V: char = '|';
 const H: char = '-';
 
-fn main() {
-    let opt = Opt::load();
-    let input = opt.input().lines();
-    let lines = opt.lines();
-    let arena = Arena::new(&lines);
+fn main() -> tools::Result<()> {
+    tools::run::<Day16>(&tools::Opt::load())
+}
 
-    let mut starting_points = vec![];
-    let mut arena_energy = vec![];
+struct Day16<'a> {
+    grid: ByteGrid<'a>,
+}
 
-    for x in 0..arena.width {
-        for y in 0..arena.height {
-            if x == 0 || x == arena.width - 1 || y == 0 || y == arena.height - 1 {
-                starting_points.push(Position::new(x, y));
-            }
-        }
-    }
+impl<'a> Solution<'a> for Day16<'a> {
+    type Part1 = usize;
+    type Part2 = usize;
 
-    for pt in &starting_points {
-        arena_energy.push((pt.clone(), calc_arena(&lines, pt.clone())));
+    fn parse(input: &'a str) -> tools::Result<Self> {
+        let grid = ByteGrid::new(input)?;
+        grid.check(|b| Tile::from_char(b as char).is_some())?;
+        Ok(Self { grid })
     }
 
-    for (s, e) in &arena_energy {
-        println!("{s} => {e}");
+    /// Beam enters the top left corner, heading east.
+    fn part1(&self) -> tools::Result<usize> {
+        let arena = Arena::new(&self.grid);
+        Ok(energize(&arena, &Position::new(0, 0), Direction::E))
     }
 
-    println!("Calculated for {} starting points.", &starting_points.len());
+    /// Beam can enter from any edge tile.
+    fn part2(&self) -> tools::Result<usize> {
+        let arena = &Arena::new(&self.grid);
+        let mut starting_points = vec![];
+        let mut arena_energy = vec![];
+
+        for x in 0..arena.width {
+            for y in 0..arena.height {
+                if x == 0 || x == arena.width - 1 || y == 0 || y == arena.height - 1 {
+                    starting_points.push(Position::new(x, y));
+                }
+            }
+        }
+
+        // With --shard, only this process's share of the start points
+        let starting_points = tools::shard::split(starting_points);
+
+        let mut progress = Progress::with_total("start points", starting_points.len());
+        for pt in &starting_points {
+            arena_energy.push((pt.clone(), calc_arena(arena, pt.clone())));
+            progress.tick();
+            progress.check()?;
+        }
+
+        for (s, e) in &arena_energy {
+            trace!("{s} => {e}");
+        }
 
-    println!(
-        "Max energy = {}",
-        arena_energy.iter().map(|o| o.1).max().unwrap()
-    );
+        debug!("Calculated for {} starting points.", &starting_points.len());
+
+        Ok(arena_energy.iter().map(|o| o.1).max().unwrap_or(0))
+    }
 }
 
 /// Runs scenario with starting tile location, and returns number of energized tiles.
-fn calc_arena(lines: &[String], starting_pos: Position) -> usize {
-    let ref_arena = Arena::new(&lines);
-
+fn calc_arena(ref_arena: &Arena, starting_pos: Position) -> usize {
+    let _span = tools::profile::span("start point");
     // Possible directions for this starting point
     let start_dirs = ref_arena.first_directions(&starting_pos);
 
     let mut total_energy: Vec<usize> = vec![];
 
     for start_dir in start_dirs {
-        let mut arena = Arena::new(&lines);
-        let first_dirs = arena.first_tile(&starting_pos, start_dir);
-        let mut starts: Vec<(Position, Direction)> = first_dirs
-            .iter()
-            .map(|d| (starting_pos.clone(), *d))
-            .collect();
+        total_energy.push(energize(ref_arena, &starting_pos, start_dir));
+    }
+    total_energy.iter().max().unwrap().clone()
+}
 
-        loop {
-            // for _ in 0..10 {
-            let mut new_starts = vec![];
-            for (start_pos, dir) in &starts {
-                let tiles = arena.get_tiles_in_direction(&start_pos, *dir);
-                if let Some(last_tile) = arena.run_through(dir, &tiles) {
-                    // let last_mirror = MirrorShape::from_char(arena.get(&last_tile)).unwrap();
-                    if let Tile::Object { shape, .. } = arena.get(&last_tile) {
-                        for new_dir in shape.redirections(&dir.reverse()) {
-                            new_starts.push((last_tile.clone(), new_dir));
-                        }
+/// Runs a single beam entering the starting tile in `start_dir`, and returns number of energized tiles.
+fn energize(ref_arena: &Arena, starting_pos: &Position, start_dir: Direction) -> usize {
+    let mut arena = ref_arena.clone();
+    let first_dirs = arena.first_tile(starting_pos, start_dir);
+    let mut starts: Vec<(Position, Direction)> = first_dirs
+        .iter()
+        .map(|d| (starting_pos.clone(), *d))
+        .collect();
+
+    loop {
+        // for _ in 0..10 {
+        let mut new_starts = vec![];
+        for (start_pos, dir) in &starts {
+            let tiles = arena.get_tiles_in_direction(&start_pos, *dir);
+            if let Some(last_tile) = arena.run_through(dir, &tiles) {
+                // let last_mirror = MirrorShape::from_char(arena.get(&last_tile)).unwrap();
+                if let Tile::Object { shape, .. } = arena.get(&last_tile) {
+                    for new_dir in shape.redirections(&dir.reverse()) {
+                        new_starts.push((last_tile.clone(), new_dir));
                     }
                 }
             }
-
-            starts = new_starts;
-
-            if starts.len() == 0 {
-                break;
-            }
         }
 
-        let total_energized = arena.total_energized();
+        starts = new_starts;
 
-        total_energy.push(total_energized)
+        if starts.len() == 0 {
+            break;
+        }
     }
-    total_energy.iter().max().unwrap().clone()
+
+    arena.total_energized()
 }
 
+#[derive(Clone)]
 struct Arena {
     data: Vec<Vec<Tile>>,
     width: usize,
@@ -98,19 +128,21 @@ struct Arena {
 }
 
 impl Arena {
-    pub fn new(lines: &[String]) -> Self {
-        let data = lines
-            .iter()
-            .map(|line| line.chars().map(|c| Tile::new(c)).collect())
+    /// Builds the tiles from a grid already checked with [`Tile::from_char`].
+    pub fn new(grid: &ByteGrid) -> Self {
+        let data = grid
+            .rows()
+            .map(|row| {
+                row.iter()
+                    .map(|b| Tile::from_char(*b as char).expect("checked when parsed"))
+                    .collect()
+            })
             .collect();
 
-        let max_width = lines[0].len();
-        let max_height = lines.len();
-
         Arena {
             data,
-            width: max_width,
-            height: max_height,
+            width: grid.width(),
+            height: grid.height(),
         }
     }
 
@@ -139,7 +171,7 @@ impl Arena {
         match self.get_mut(&first_pos) {
             Tile::Object { shape, energized } => {
                 *energized = true;
-                shape.redirections(&first_dir)
+                shape.redirections(&first_dir.reverse())
             }
             Tile::Space(set) => {
                 set.push(first_dir);
@@ -289,7 +321,7 @@ impl Arena {
             }
             s.push_str("\n");
         }
-        print!("{}", s);
+        debug!("{}", s.trim_end());
     }
 
     pub fn print_energized(&self) {
@@ -309,25 +341,27 @@ impl Arena {
             }
             s.push_str("\n");
         }
-        print!("{}", s);
+        debug!("{}", s.trim_end());
     }
 }
 
+#[derive(Clone)]
 enum Tile {
     Object { shape: MirrorShape, energized: bool },
     Space(Vec<Direction>),
 }
 
 impl Tile {
-    pub fn new(c: char) -> Self {
+    /// Parses a tile character, returning `None` if it isn't a valid tile.
+    pub fn from_char(c: char) -> Option<Self> {
         if c == '.' {
-            Self::Space(Vec::new())
+            Some(Self::Space(Vec::new()))
         } else {
-            let shape = MirrorShape::from_char(c).unwrap();
-            Self::Object {
+            let shape = MirrorShape::from_char(c)?;
+            Some(Self::Object {
                 shape,
                 energized: false,
-            }
+            })
         }
     }
 }
@@ -474,3 +508,22 @@ impl MirrorShape {
 //         Display::fmt(self, f)
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn example() {
+        let day = Day16::parse(include_str!("../example.txt")).unwrap();
+        assert_eq!(day.part1().unwrap(), 46);
+        assert_eq!(day.part2().unwrap(), 51);
+    }
+
+    #[test]
+    pub fn mirror_on_first_tile() {
+        // Heading east into `\` turns south, down the first column
+        let day = Day16::parse("\\.\n..\n").unwrap();
+        assert_eq!(day.part1().unwrap(), 2);
+    }
+}
@@ -1,46 +1,74 @@
 use std::fmt::Display;
 
 use pathfinding::directed::dijkstra::dijkstra;
-use tools::{Arena, Direction, Opt, Position};
-
-fn main() {
-    let opt = Opt::load();
-    let input = opt.input().lines();
-    let lines = opt.lines();
-
-    let data: Vec<Vec<u8>> = lines
-        .iter()
-        .map(|l| {
-            l.chars()
-                .map(|c| c.to_string().parse::<u8>().unwrap())
-                .collect()
+use tools::input::ByteGrid;
+use tools::{debug, Arena, Direction, Error, Position, Solution};
+
+fn main() -> tools::Result<()> {
+    tools::run::<Day17>(&tools::Opt::load())
+}
+
+struct Day17 {
+    darena: DArena,
+}
+
+impl Solution<'_> for Day17 {
+    type Part1 = u32;
+    type Part2 = u32;
+
+    fn parse(input: &str) -> tools::Result<Self> {
+        let grid = ByteGrid::new(input)?;
+        grid.check(|b| b.is_ascii_digit())?;
+        let arena = Arena::new(
+            grid.rows()
+                .map(|row| row.iter().map(|b| b - b'0').collect())
+                .collect(),
+        );
+        Ok(Self {
+            darena: DArena { arena },
         })
-        .collect();
+    }
 
-    let arena = Arena::new(data);
-    let darena = DArena { arena };
+    fn part1(&self) -> tools::Result<u32> {
+        let darena = &self.darena;
+        let start = Position::new(0, 0);
+        let goal = Position::new(darena.arena.width() - 1, darena.arena.height() - 1);
 
-    let start = Position::new(0, 0);
-    let goal = Position::new(darena.arena.width() - 1, darena.arena.height() - 1);
+        let result = dijkstra(&start, |p| darena.successors(p), |p| *p == goal);
 
-    let result = dijkstra(&start, |p| darena.successors(p), |p| *p == goal);
+        let Some((path, score)) = result else {
+            return Err(Error::validation("no path to the goal"));
+        };
 
-    if let Some((path, score)) = result {
         let min_heat_loss = score;
-        darena.arena.print(|pos, val| if path.contains(pos) { '#' } else { '.' });
+        debug!(
+            "{}",
+            darena
+                .arena
+                .render(|pos, _| if path.contains(pos) { '#' } else { '.' })
+                .trim_end()
+        );
 
-        println!("Heat Loss = {min_heat_loss}");
+        Ok(min_heat_loss)
+    }
+
+    fn part2(&self) -> tools::Result<u32> {
+        Err(Error::Unsolved)
     }
 }
 
 struct DArena {
     arena: Arena<u8>,
-
 }
 
 impl DArena {
-    pub fn successors(&self, start: &Position) -> Vec<(Position, u8)> {
+    /// Gets the tiles next to `start`, with the heat lost entering each.
+    pub fn successors(&self, start: &Position) -> Vec<(Position, u32)> {
         // Run local dijkstra up to 4th place neighbor
         let neighbors = self.arena.get_neighbors(start);
+        neighbors
+            .into_iter()
+            .map(|(pos, heat)| (pos, heat as u32))
+            .collect()
     }
 }
@@ -3,25 +3,46 @@ use std::{
     fmt::{Debug, Display},
 };
 
-use tools::Opt;
+use tools::{input, trace, Error, Solution};
 
-fn main() {
-    let opt = Opt::load();
-    let input = opt.input();
-    let lines: Vec<&str> = input.lines().collect();
+fn main() -> tools::Result<()> {
+    tools::run::<Day4>(&tools::Opt::load())
+}
+
+struct Day4 {
+    cards: Vec<Card>,
+}
+
+impl Solution<'_> for Day4 {
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> tools::Result<Self> {
+        let cards = input::lines(input)
+            .enumerate()
+            .map(|(i, l)| Card::new(l).map_err(|e| e.at_line(i + 1)))
+            .collect::<tools::Result<Vec<_>>>()?;
+
+        Ok(Self { cards })
+    }
 
-    let mut state = Problem::new();
-    state.populate_cards(&lines.iter().map(|l| Card::new(l)).collect::<Vec<_>>());
-    state.total_winnings();
+    fn part1(&self) -> tools::Result<usize> {
+        let score = self
+            .cards
+            .iter()
+            .map(|c| if c.points > 0 { 1 << (c.points - 1) } else { 0 })
+            .sum();
 
-    // for (i, t) in &state.copies {
-    //     println!("Card [{}] = {t:?}", i);
-    // }
+        Ok(score)
+    }
+
+    fn part2(&self) -> tools::Result<usize> {
+        let mut state = Problem::new();
+        state.populate_cards(&self.cards);
+        state.total_winnings();
 
-    println!(
-        "Total = {}",
-        state.copies.iter().sum::<usize>()
-    );
+        Ok(state.copies.iter().sum::<usize>())
+    }
 }
 
 struct Problem {
@@ -72,14 +93,14 @@ impl Problem {
         // Check if already calculated
 
         let won_card_range = card_idx + 1..self.cards.len().min(card_idx + 1 + card);
-        println!("[{card_idx}] + {} -> {won_card_range:?}", card);
+        trace!("[{card_idx}] + {} -> {won_card_range:?}", card);
         // Start by counting self
         let mut won_cards = 1;
         for i in won_card_range.clone() {
             // Count won cards
             won_cards += self.winnings(i);
         }
-        println!("[{card_idx}] = {won_card_range:?} ({})", won_cards);
+        trace!("[{card_idx}] = {won_card_range:?} ({})", won_cards);
         self.copies.insert(card_idx, won_cards);
         won_cards
     }
@@ -99,7 +120,6 @@ impl Problem {
 //     if !map.contains_key(&card_idx) {
 //         return;
 //     }
-//     // println!("Process({prev_pts}): {card_idx}");
 //     *prev_pts += 1;
 //     let curr_pts = map
 //         .get(&card_idx)
@@ -121,30 +141,34 @@ struct Card {
 }
 
 impl Card {
-    pub fn new(line: &str) -> Self {
-        let vert_parts: Vec<&str> = line.split('|').collect();
-
-        let wins: Vec<usize> = vert_parts[0]
-            .split(' ')
-            .filter(|s| !s.is_empty())
-            .skip(2)
-            .map(|s| s.parse::<usize>().unwrap())
-            .collect();
-        let mine: Vec<usize> = vert_parts[1]
-            .split(' ')
-            .filter(|s| !s.is_empty())
-            .map(|s| s.parse::<usize>().unwrap())
-            .collect();
+    pub fn new(line: &str) -> tools::Result<Self> {
+        let Some((left, right)) = line.split_once('|') else {
+            return Err(Error::parse(1, "expected `|` between number lists"));
+        };
+
+        let (_, wins) = input::labeled_ints::<usize>(left)?;
+        let mine = input::ints::<usize>(right).map_err(|e| e.offset_column(left.len() + 1))?;
 
         let points = Self::points(wins, mine);
 
-        Self { points }
+        Ok(Self { points })
     }
 
     pub fn points(wins: Vec<usize>, mine: Vec<usize>) -> usize {
         let num = mine.iter().filter(|n| wins.contains(n)).count();
         // let pts = if num > 0 { 1 << (num - 1) } else { 0 };
-        // println!("Num of winning cards = {num}, Points = {pts}");
         num
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn example() {
+        let day = Day4::parse(include_str!("../example.txt")).unwrap();
+        assert_eq!(day.part1().unwrap(), 13);
+        assert_eq!(day.part2().unwrap(), 30);
+    }
+}
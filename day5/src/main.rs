@@ -1,94 +1,136 @@
 use ranges::{GenericRange, OperationResult, Ranges};
-use tools::Opt;
+use tools::checkpoint::Checkpoint;
+use tools::profile;
+use tools::{input, AocParse, Error, Solution};
 
 use std::{
     collections::HashMap,
     fmt::Display,
+    num::ParseIntError,
     ops::{Bound, RangeBounds},
-    time::Instant,
+    str::FromStr,
 };
 
 use ranges::GenericRange as Range;
 type Rg = Range<u64>;
 type Rgs = Ranges<u64>;
 
-fn main() {
-    // let args: Vec<String> = std::env::args().collect();
+fn main() -> tools::Result<()> {
+    tools::run::<Day5>(&tools::Opt::load())
+}
+
+struct Day5 {
+    seeds: Vec<u64>,
+    sections: Vec<Section>,
+}
+
+impl Solution<'_> for Day5 {
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> tools::Result<Self> {
+        let section_txts: Vec<&str> = input::blocks(input).collect();
+
+        let seeds_txt = section_txts
+            .first()
+            .ok_or_else(|| Error::validation("missing seeds line"))?;
+        let (_, seeds) = input::labeled_ints::<u64>(seeds_txt)?;
 
-    let opt = Opt::load();
-    // let is_part2 = args.contains(&"part2".to_string());
-    // let is_example = args.contains(&"example".to_string());
+        let sections: Vec<Section> = section_txts
+            .iter()
+            .skip(1)
+            .map(|st| Section::new(st).map_err(|e| e.offset_line(input::line_of(input, st) - 1)))
+            .collect::<tools::Result<_>>()?;
 
+        Ok(Self { seeds, sections })
+    }
 
-    let input = std::fs::read_to_string(&opt.file()).unwrap();
-    let section_txts: Vec<&str> = input.split("\r\n\r\n").collect();
+    fn part1(&self) -> tools::Result<u64> {
+        let seed_ranges: Vec<Rg> = self
+            .seeds
+            .iter()
+            .map(|&s| seed_range(s, 1))
+            .collect::<tools::Result<_>>()?;
 
-    let seeds: Vec<u64> = section_txts
-        .first()
-        .unwrap()
-        .split(" ")
-        .skip(1)
-        .map(|s| s.parse::<u64>().unwrap())
-        .collect();
+        self.closest_location("part1", seed_ranges)
+    }
 
-    let seed_ranges: Vec<Rg> = if !opt.is_part1 {
-        seeds
+    fn part2(&self) -> tools::Result<u64> {
+        // The seeds are `start len` pairs here
+        if self.seeds.len() % 2 != 0 {
+            return Err(Error::validation(format!(
+                "expected seed pairs, found {} seeds",
+                self.seeds.len()
+            )));
+        }
+        let seed_ranges: Vec<Rg> = self
+            .seeds
             .chunks(2)
             .map(|c| {
                 let start = c[0];
                 let len = c[1];
 
-                (start..(start + len)).into()
+                seed_range(start, len)
             })
-            .collect()
-    } else {
-        seeds.iter().map(|&s| (s..s + 1).into()).collect()
-    };
-
-    // dbg!(&seed_ranges);
-    let section_rest: Vec<&&str> = section_txts.iter().skip(1).collect();
-
-    let sections: Vec<Section> = section_rest.iter().map(|st| Section::new(st)).collect();
-
-    // dbg!(sections);
-
-    let soil = sections.iter().find(|s| s.title.dst == "soil").unwrap();
-    let fertilizer = sections
-        .iter()
-        .find(|s| s.title.dst == "fertilizer")
-        .unwrap();
-    let water = sections.iter().find(|s| s.title.dst == "water").unwrap();
-    let light: &Section = sections.iter().find(|s| s.title.dst == "light").unwrap();
-    let temperature = sections
-        .iter()
-        .find(|s| s.title.dst == "temperature")
-        .unwrap();
-    let humidity = sections.iter().find(|s| s.title.dst == "humidity").unwrap();
-    let location = sections.iter().find(|s| s.title.dst == "location").unwrap();
-
-    let mut locations = Rgs::new();
-
-    for seed_range in seed_ranges {
-        let start = Instant::now();
-        let seed = Rgs::from(seed_range);
-        let soil = soil.lookup_ranges(seed);
-        let fert = fertilizer.lookup_ranges(soil);
-        let water = water.lookup_ranges(fert);
-        let light = light.lookup_ranges(water);
-        let temp = temperature.lookup_ranges(light);
-        let hum = humidity.lookup_ranges(temp);
-        let loc = location.lookup_ranges(hum);
-
-        // println!("Seed {seed} -> Location {loc}");
-
-        locations = locations.union(loc);
-        let end = Instant::now();
-        println!("{:?}", end - start);
+            .collect::<tools::Result<_>>()?;
+
+        self.closest_location("part2", seed_ranges)
     }
+}
 
-    let closest = find_smallest_in_ranges(locations);
+/// Gets the `len` seeds from `start`, failing if they run past `u64::MAX`.
+fn seed_range(start: u64, len: u64) -> tools::Result<Rg> {
+    let end = start
+        .checked_add(len)
+        .ok_or_else(|| Error::validation(format!("seed range {start} {len} overflows")))?;
+    Ok((start..end).into())
+}
 
-    println!("Closest location = {closest}");
+impl Day5 {
+    /// Finds a section by its destination name.
+    fn section(&self, dst: &str) -> tools::Result<&Section> {
+        self.sections
+            .iter()
+            .find(|s| s.title.dst == dst)
+            .ok_or_else(|| Error::validation(format!("missing map to {dst}")))
+    }
+
+    /// Maps each seed range through every section, and finds the closest location.
+    /// With `--shard`, only this process's share of the seed ranges is searched.
+    /// Checkpoints `(next seed range, closest location so far)` under `name`, for `--resume`.
+    fn closest_location(&self, name: &str, seed_ranges: Vec<Rg>) -> tools::Result<u64> {
+        let soil = self.section("soil")?;
+        let fertilizer = self.section("fertilizer")?;
+        let water = self.section("water")?;
+        let light = self.section("light")?;
+        let temperature = self.section("temperature")?;
+        let humidity = self.section("humidity")?;
+        let location = self.section("location")?;
+
+        let seed_ranges = tools::shard::split(seed_ranges);
+        let mut checkpoint = Checkpoint::<(usize, u64)>::new(name);
+        let (next, mut closest) = checkpoint.resume()?.unwrap_or((0, u64::MAX));
+
+        for (i, seed_range) in seed_ranges.into_iter().enumerate().skip(next) {
+            tools::cancel::check()?;
+            let _span = profile::span("seed range");
+
+            let seed = Rgs::from(seed_range);
+            let soil = soil.lookup_ranges(seed);
+            let fert = fertilizer.lookup_ranges(soil);
+            let water = water.lookup_ranges(fert);
+            let light = light.lookup_ranges(water);
+            let temp = temperature.lookup_ranges(light);
+            let hum = humidity.lookup_ranges(temp);
+            let loc = location.lookup_ranges(hum);
+
+            closest = closest.min(find_smallest_in_ranges(loc));
+            checkpoint.tick(|| (i + 1, closest))?;
+        }
+
+        checkpoint.finish()?;
+        Ok(closest)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -97,22 +139,13 @@ struct Section {
     ranges: Vec<SectionRange>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, AocParse)]
+#[aoc(format = "{src}-to-{dst} map:")]
 struct SectionTitle {
     src: String,
     dst: String,
 }
 
-impl SectionTitle {
-    pub fn new(line: &str) -> SectionTitle {
-        let parts: Vec<&str> = line.split(&[' ', '-']).collect();
-        SectionTitle {
-            src: parts[0].to_owned(),
-            dst: parts[2].to_owned(),
-        }
-    }
-}
-
 impl Display for SectionTitle {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}-to-{} map", self.src, self.dst)
@@ -120,20 +153,23 @@ impl Display for SectionTitle {
 }
 
 impl Section {
-    pub fn new(section_txt: &str) -> Self {
-        let lines: Vec<&str> = section_txt.split("\r\n").collect();
-        let title = SectionTitle::new(lines.first().unwrap());
+    /// Parses a section block, with errors at lines relative to the block.
+    pub fn new(section_txt: &str) -> tools::Result<Self> {
+        let lines: Vec<&str> = input::lines(section_txt).collect();
+        let title = lines[0].parse::<SectionTitle>().map_err(|e| e.at_line(1))?;
 
         let mut ranges = vec![];
 
-        for &line in lines.iter().skip(1) {
+        for (i, &line) in lines.iter().enumerate().skip(1) {
             if !line.is_empty() {
-                let range = SectionRange::new(line);
+                let range = line.parse::<SectionRange>().map_err(|e| {
+                    Error::parse_in(line, e.span(line), e.to_string()).at_line(i + 1)
+                })?;
                 ranges.push(range);
             }
         }
 
-        Section { title, ranges }
+        Ok(Section { title, ranges })
     }
 
     /// Finds a single seed in this section
@@ -142,16 +178,16 @@ impl Section {
             if range.src_range.contains(&input) {
                 let offset = input - range.source;
                 let output = range.destination + offset;
-                // println!("{}: {} -> {} -> {}", self.title, input, range, output);
                 return output as u64;
             }
         }
-        // println!("{}: not found: {} -> {}", self.title, input, input);
         input
     }
 
     /// Finds all mapped ranges for the provided seed range.
     pub fn lookup_ranges(&self, input: Rgs) -> Rgs {
+        // Named after the destination, e.g. `soil`, to tell the chained lookups apart
+        let _span = profile::span(&self.title.dst);
         let mut mapped_pairs: Vec<(Rgs, Rgs)> = vec![];
 
         // Find where input intersects with map
@@ -203,24 +239,80 @@ struct SectionRange {
     dst_range: Rg,
 }
 
-impl SectionRange {
-    pub fn new(line: &str) -> Self {
-        let parts: Vec<&str> = line.split(" ").collect();
-        assert!(parts.len() == 3, "parts={:?}", parts);
+/// Why a line is not a `<destination> <source> <length>` range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ParseRangeError {
+    /// Number of values, when not 3.
+    WrongCount(usize),
+    /// The value at `index` is not a number.
+    InvalidNumber {
+        index: usize,
+        name: &'static str,
+        error: ParseIntError,
+    },
+    /// The source or destination range goes past `u64::MAX`.
+    Overflow,
+}
+
+impl ParseRangeError {
+    /// Gets the part of `line` this error is about.
+    fn span<'a>(&self, line: &'a str) -> &'a str {
+        match self {
+            ParseRangeError::InvalidNumber { index, .. } => {
+                line.split(' ').nth(*index).unwrap_or(line)
+            }
+            _ => line,
+        }
+    }
+}
+
+impl Display for ParseRangeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseRangeError::WrongCount(count) => write!(
+                f,
+                "expected `<destination> <source> <length>`, found {count} values"
+            ),
+            ParseRangeError::InvalidNumber { name, error, .. } => {
+                write!(f, "invalid {name}: {error}")
+            }
+            ParseRangeError::Overflow => write!(f, "range is too long"),
+        }
+    }
+}
 
-        let destination = parts[0].parse::<u64>().expect("Invalid destination number");
-        let source = parts[1].parse::<u64>().expect("Invalid source number");
-        let margin = parts[2].parse::<u64>().expect("Invalid margin number");
+impl std::error::Error for ParseRangeError {}
 
-        Self {
+impl FromStr for SectionRange {
+    type Err = ParseRangeError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = line.split(' ').collect();
+        if parts.len() != 3 {
+            return Err(ParseRangeError::WrongCount(parts.len()));
+        }
+
+        let number = |index: usize, name: &'static str| {
+            parts[index]
+                .parse::<u64>()
+                .map_err(|error| ParseRangeError::InvalidNumber { index, name, error })
+        };
+        let destination = number(0, "destination")?;
+        let source = number(1, "source")?;
+        let margin = number(2, "length")?;
+        let end = |start: u64| start.checked_add(margin).ok_or(ParseRangeError::Overflow);
+
+        Ok(Self {
             source,
             destination,
             margin,
-            src_range: (source..source + margin).into(),
-            dst_range: (destination..destination + margin).into(),
-        }
+            src_range: (source..end(source)?).into(),
+            dst_range: (destination..end(destination)?).into(),
+        })
     }
+}
 
+impl SectionRange {
     /// Gets the offset of the destination relative to source.
     pub fn offset(&self) -> i64 {
         self.destination as i64 - self.source as i64
@@ -262,3 +354,46 @@ impl Display for SectionRange {
         write!(f, "({:?} / {:?})", self.src_range, self.dst_range)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn section_ranges() {
+        let range: SectionRange = "50 98 2".parse().unwrap();
+        assert_eq!((range.destination, range.source, range.margin), (50, 98, 2));
+        assert_eq!(range.offset(), -48);
+
+        assert_eq!(
+            "50 98".parse::<SectionRange>(),
+            Err(ParseRangeError::WrongCount(2))
+        );
+        let e = "50 x8 2".parse::<SectionRange>().unwrap_err();
+        assert_eq!(e.span("50 x8 2"), "x8");
+        assert_eq!(
+            e.to_string(),
+            "invalid source: invalid digit found in string"
+        );
+        assert_eq!(
+            format!("{} 1 2", u64::MAX).parse::<SectionRange>(),
+            Err(ParseRangeError::Overflow)
+        );
+    }
+
+    #[test]
+    pub fn seed_pairs() {
+        // Only part 2 reads the seeds as pairs
+        let day = Day5::parse("seeds: 79 14 55\n").unwrap();
+        let e = day.part2().unwrap_err();
+        let expected = Error::validation("expected seed pairs, found 3 seeds");
+        assert_eq!(e.to_string(), expected.to_string());
+
+        let seeds = format!("seeds: {} 1\n", u64::MAX);
+        let day = Day5::parse(&seeds).unwrap();
+        let expected = format!("seed range {} 1 overflows", u64::MAX);
+        let expected = Error::validation(expected).to_string();
+        assert_eq!(day.part1().unwrap_err().to_string(), expected);
+        assert_eq!(day.part2().unwrap_err().to_string(), expected);
+    }
+}
@@ -1,81 +1,78 @@
 use std::collections::HashMap;
 
-use tools::Opt;
-
-fn main() {
-    let opt = Opt::load();
-
-    let input = opt.input();
-    let mut lines = input.lines();
-    if opt.is_part1 {
-        let time_line = lines.next().unwrap();
-        let distance_line = lines.next().unwrap();
-
-        let times: Vec<u64> = time_line
-            .split(&[' '])
-            .filter(|s| !s.is_empty())
-            .skip(1)
-            .map(|s| s.parse::<u64>().unwrap())
-            .collect();
-        let distances: Vec<u64> = distance_line
-            .split(&[' '])
-            .filter(|s| !s.is_empty())
-            .skip(1)
-            .map(|s| s.parse::<u64>().unwrap())
-            .collect();
+use tools::{debug, input, trace, Error, Solution};
+
+fn main() -> tools::Result<()> {
+    tools::run::<Day6>(&tools::Opt::load())
+}
+
+struct Day6 {
+    times: Vec<u64>,
+    distances: Vec<u64>,
+}
+
+impl Solution<'_> for Day6 {
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> tools::Result<Self> {
+        let mut lines = input::lines(input);
+        let time_line = lines
+            .next()
+            .ok_or_else(|| Error::validation("missing time line"))?;
+        let distance_line = lines
+            .next()
+            .ok_or_else(|| Error::validation("missing distance line"))?;
+
+        let (_, times) = input::labeled_ints::<u64>(time_line)?;
+        let (_, distances) =
+            input::labeled_ints::<u64>(distance_line).map_err(|e| e.at_line(2))?;
+
+        Ok(Self { times, distances })
+    }
 
-        let races: Vec<Race> = times
+    fn part1(&self) -> tools::Result<usize> {
+        let races: Vec<Race> = self
+            .times
             .iter()
-            .zip(distances.iter())
+            .zip(self.distances.iter())
             .map(|(t, d)| Race {
                 time: *t,
                 record_distance: *d,
             })
             .collect();
 
-        println!("Races = {:#?}", races);
+        debug!("Races = {:#?}", races);
 
         let mut runs = 1;
         for race in races {
-            let sufficient_runs = dbg!(race.get_sufficient_runs());
+            let sufficient_runs = race.get_sufficient_runs();
+            trace!("{race:?} => {sufficient_runs:?}");
             runs *= sufficient_runs.len();
         }
 
-        println!("Runs = {runs}");
+        Ok(runs)
     }
-    // Part 2
-    else {
-        let time_line = lines.next().unwrap();
-        let distance_line = lines.next().unwrap();
-
-        let time: u64 = time_line
-            .split(&[' '])
-            .filter(|s| !s.is_empty())
-            .skip(1)
-            .fold(String::new(), |mut a, b| {
-                a.push_str(b);
-                a
-            })
-            .parse::<u64>()
-            .unwrap();
-        let distance_record: u64 = distance_line
-            .split(&[' '])
-            .filter(|s| !s.is_empty())
-            .skip(1)
-            .fold(String::new(), |mut a, b| {
-                a.push_str(b);
-                a
-            })
-            .parse::<u64>()
-            .unwrap();
 
-        let race = Race { time, record_distance: distance_record };
-        let runs = race.get_sufficient_runs().len();
+    fn part2(&self) -> tools::Result<usize> {
+        let time = join_digits(&self.times)?;
+        let distance_record = join_digits(&self.distances)?;
 
-        println!("Runs = {runs}");
+        let race = Race { time, record_distance: distance_record };
+        Ok(race.get_sufficient_runs().len())
     }
 }
 
+/// Joins the digits of all values into one number, ignoring the spacing between them.
+fn join_digits(values: &[u64]) -> tools::Result<u64> {
+    values
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<String>()
+        .parse::<u64>()
+        .map_err(|e| Error::validation(format!("joined number is invalid: {e}")))
+}
+
 #[derive(Debug)]
 struct Race {
     time: u64,
@@ -108,3 +105,15 @@ impl Race {
         max_button_presses
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn example() {
+        let day = Day6::parse(include_str!("../example.txt")).unwrap();
+        assert_eq!(day.part1().unwrap(), 288);
+        assert_eq!(day.part2().unwrap(), 71503);
+    }
+}
@@ -1,35 +1,142 @@
-use std::{cmp::Ordering, collections::HashMap, fmt::Display};
+use std::{cmp::Ordering, collections::HashMap, fmt::Display, str::FromStr};
 
 use itertools::Itertools;
-use tools::Opt;
+use tools::{input, trace, Error, Solution};
 
-/// PART 2
-fn main() {
-    let opt = Opt::load();
-    let input = opt.input();
-    let lines = input.lines();
+mod part1;
 
-    let mut games: Vec<Game> = lines
-        .map(|line| {
-            let parts: Vec<&str> = line.split(" ").collect();
-            let hand = Hand::new(parts[0]);
-            let bid = parts[1].parse::<usize>().unwrap();
+fn main() -> tools::Result<()> {
+    tools::run::<Day7>(&tools::Opt::load())
+}
+
+struct Day7<'a> {
+    /// Hand and bid of each game
+    rows: Vec<(&'a str, usize)>,
+}
+
+impl<'a> Solution<'a> for Day7<'a> {
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &'a str) -> tools::Result<Self> {
+        let rows = input::lines(input)
+            .enumerate()
+            .map(|(i, line)| {
+                let Some((hand, bid)) = line.split_once(' ') else {
+                    return Err(Error::parse(i + 1, "expected `<hand> <bid>`"));
+                };
+                check_hand(line, hand).map_err(|e| e.at_line(i + 1))?;
+                let bid = bid.parse::<usize>().map_err(|e| {
+                    Error::parse_in(line, bid, format!("invalid bid: {e}")).at_line(i + 1)
+                })?;
+
+                Ok((hand, bid))
+            })
+            .collect::<tools::Result<Vec<_>>>()?;
+
+        Ok(Self { rows })
+    }
+
+    /// `J` is a Jack.
+    fn part1(&self) -> tools::Result<usize> {
+        part1::total_score(&self.rows)
+    }
+
+    /// `J` is a Joker, which acts as a wildcard, and is the weakest card.
+    fn part2(&self) -> tools::Result<usize> {
+        let mut games: Vec<Game> = self
+            .rows
+            .iter()
+            .map(|(hand, bid)| {
+                Ok(Game {
+                    hand: hand.parse()?,
+                    bid: *bid,
+                })
+            })
+            .collect::<tools::Result<_>>()?;
+
+        games.sort_by(|a, b| a.hand.cmp(&b.hand));
+        let mut total_score = 0;
+        for (i, game) in games.iter().enumerate() {
+            let rank = i + 1;
+            let score = rank * game.bid;
+            total_score += score;
+        }
+
+        Ok(total_score)
+    }
+}
+
+/// Checks a hand has five valid cards, so both parts can score it.
+fn check_hand(line: &str, hand: &str) -> tools::Result<()> {
+    hand.parse::<Hand>()
+        .map(drop)
+        .map_err(|e| Error::parse_in(line, e.span(hand), e.to_string()))
+}
+
+/// A character that isn't one of `23456789TJQKA`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct InvalidCard(char);
+
+impl Display for InvalidCard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid card {:?}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidCard {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ParseHandError {
+    /// The card starting at byte `index` of the hand is invalid.
+    InvalidCard { index: usize, card: InvalidCard },
+    /// Number of cards, when not 5.
+    WrongCount(usize),
+}
 
-            Game { hand, bid }
+impl ParseHandError {
+    /// Gets the part of `hand` this error is about.
+    fn span<'a>(&self, hand: &'a str) -> &'a str {
+        match self {
+            ParseHandError::InvalidCard { index, card } => &hand[*index..index + card.0.len_utf8()],
+            ParseHandError::WrongCount(_) => hand,
+        }
+    }
+}
+
+impl Display for ParseHandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseHandError::InvalidCard { card, .. } => Display::fmt(card, f),
+            ParseHandError::WrongCount(count) => write!(f, "expected 5 cards, found {count}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseHandError {}
+
+impl From<ParseHandError> for Error {
+    fn from(e: ParseHandError) -> Self {
+        Error::validation(e.to_string())
+    }
+}
+
+/// Reads the 5 cards of a hand, for either part's cards.
+fn parse_cards<C>(hand: &str) -> Result<Vec<C>, ParseHandError>
+where
+    C: TryFrom<char, Error = InvalidCard>,
+{
+    let cards = hand
+        .char_indices()
+        .map(|(index, c)| {
+            C::try_from(c).map_err(|card| ParseHandError::InvalidCard { index, card })
         })
-        .collect();
-
-    // println!("Games = {games:#?}");
-    games.sort_by(|a, b| a.hand.cmp(&b.hand));
-    let mut total_score = 0;
-    for (i, game) in games.iter().enumerate() {
-        let rank = i + 1;
-        let score = rank * game.bid;
-        total_score += score;
-        // println!("#{rank}: {game:?} = {score}");
+        .collect::<Result<Vec<C>, _>>()?;
+    if cards.len() != 5 {
+        return Err(ParseHandError::WrongCount(cards.len()));
     }
 
-    println!("Total score = {total_score}");
+    Ok(cards)
 }
 
 #[derive(Debug)]
@@ -83,9 +190,11 @@ impl std::fmt::Debug for Card {
     }
 }
 
-impl From<char> for Card {
-    fn from(value: char) -> Self {
-        match value {
+impl TryFrom<char> for Card {
+    type Error = InvalidCard;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        Ok(match value {
             '2' => Card::C2,
             '3' => Card::C3,
             '4' => Card::C4,
@@ -99,8 +208,8 @@ impl From<char> for Card {
             'Q' => Card::Q,
             'K' => Card::K,
             'A' => Card::A,
-            _ => panic!("Invalid card character: {}", value),
-        }
+            _ => return Err(InvalidCard(value)),
+        })
     }
 }
 
@@ -120,12 +229,14 @@ struct Hand {
     hand_type: HandType,
 }
 
-impl Hand {
-    pub fn new(input_str: &str) -> Self {
-        let cards: Vec<Card> = input_str.chars().map(|c| Card::from(c)).collect();
+impl FromStr for Hand {
+    type Err = ParseHandError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cards: Vec<Card> = parse_cards(s)?;
         let hand_type = HandType::new(&cards);
 
-        Hand { cards, hand_type }
+        Ok(Hand { cards, hand_type })
     }
 }
 
@@ -161,7 +272,7 @@ impl Ord for Hand {
                 }
             }
             if self.cards.contains(&Card::J) || other.cards.contains(&Card::J) {
-                println!(
+                trace!(
                     "Tie breaking Jokers: A: {:?} <--> B: {:?} = {:?}",
                     self.cards, other.cards, cmp
                 );
@@ -302,7 +413,7 @@ impl HandType {
             .map(|(k, v)| (k.clone(), v.clone()))
             .collect::<Vec<(Card, usize)>>();
 
-        println!("sorted (pre J) = {sorted:?}");
+        trace!("sorted (pre J) = {sorted:?}");
 
         // Convert joker count to highest frequency card
         if let Some(joker_count) = &card_counts.get(&Card::J) {
@@ -321,7 +432,7 @@ impl HandType {
             }
         }
 
-        println!("sorted (post J) = {sorted:?}");
+        trace!("sorted (post J) = {sorted:?}");
         sorted
     }
 }
@@ -398,6 +509,13 @@ impl PartialEq for HandType {
 mod tests {
     use super::*;
 
+    #[test]
+    pub fn example() {
+        let day = Day7::parse(include_str!("../example.txt")).unwrap();
+        assert_eq!(day.part1().unwrap(), 6440);
+        assert_eq!(day.part2().unwrap(), 5905);
+    }
+
     #[test]
     pub fn hand_types() {
         let input = HashMap::from([
@@ -445,7 +563,7 @@ mod tests {
         ]);
 
         for (s, h) in input {
-            let hand = Hand::new(s);
+            let hand = s.parse::<Hand>().unwrap();
 
             assert_eq!(hand.hand_type, h);
         }
@@ -453,8 +571,8 @@ mod tests {
 
     #[test]
     pub fn tie_break_joker() {
-        let a = Hand::new("JKKK2");
-        let b = Hand::new("QQQQ2");
+        let a = "JKKK2".parse::<Hand>().unwrap();
+        let b = "QQQQ2".parse::<Hand>().unwrap();
 
         let cmp = a.cmp(&b);
 
@@ -463,17 +581,41 @@ mod tests {
 
     #[test]
     pub fn five_joker() {
-        let jokers = Hand::new("JJJJJ");
-        let kings = Hand::new("KKKKK");
+        let jokers = "JJJJJ".parse::<Hand>().unwrap();
+        let kings = "KKKKK".parse::<Hand>().unwrap();
 
         assert!(jokers < kings);
     }
 
     #[test]
     pub fn four_joker_tie_breaker() {
-        let jokers = dbg!(Hand::new("QJJJT"));
-        let other = dbg!(Hand::new("KQQQQ"));
+        let jokers = dbg!("QJJJT".parse::<Hand>().unwrap());
+        let other = dbg!("KQQQQ".parse::<Hand>().unwrap());
 
         assert!(jokers < other);
     }
+
+    #[test]
+    pub fn invalid_hands() {
+        assert_eq!(Card::try_from('Z'), Err(InvalidCard('Z')));
+        assert_eq!(
+            "32X3K".parse::<Hand>().unwrap_err(),
+            ParseHandError::InvalidCard {
+                index: 2,
+                card: InvalidCard('X')
+            }
+        );
+        assert_eq!(
+            "32T3".parse::<Hand>().unwrap_err(),
+            ParseHandError::WrongCount(4)
+        );
+        assert_eq!(
+            "32T3KK".parse::<Hand>().unwrap_err().to_string(),
+            "expected 5 cards, found 6"
+        );
+
+        let e = Day7::parse("32T3K 765\nT5é5J 684\n").err().unwrap();
+        let e = e.to_string();
+        assert!(e.starts_with("<input>:2:3: parse error: invalid card 'é'"), "{e}");
+    }
 }
@@ -1,36 +1,33 @@
-use std::{cmp::Ordering, collections::HashMap};
+use std::{cmp::Ordering, collections::HashMap, str::FromStr};
 
 use itertools::Itertools;
-use tools::Opt;
-
-fn main() {
-    let opt = Opt::load();
-    let input = opt.input();
-    let lines = input.lines();
-
-    let mut games: Vec<Game> = lines
-        .map(|line| {
-            let parts: Vec<&str> = line.split(" ").collect();
-            let hand = Hand::new(parts[0]);
-            let bid = parts[1].parse::<usize>().unwrap();
-
-            Game { hand, bid }
+use tools::{debug, trace};
+
+use super::{parse_cards, InvalidCard, ParseHandError};
+
+/// Scores all hands, where `J` is a Jack.
+pub fn total_score(rows: &[(&str, usize)]) -> tools::Result<usize> {
+    let mut games: Vec<Game> = rows
+        .iter()
+        .map(|(hand, bid)| {
+            Ok(Game {
+                hand: hand.parse()?,
+                bid: *bid,
+            })
         })
-        .collect();
+        .collect::<tools::Result<_>>()?;
 
-    println!("Games = {games:#?}");
+    debug!("Games = {games:#?}");
     games.sort_by(|a, b| a.hand.cmp(&b.hand));
     let mut total_score = 0;
     for (i, game) in games.iter().enumerate() {
         let rank = i + 1;
         let score = rank * game.bid;
         total_score += score;
-        println!("#{rank}: {game:?} = {score}");
+        trace!("#{rank}: {game:?} = {score}");
     }
 
-    println!("Total score = {total_score}");
-    
-    
+    Ok(total_score)
 }
 
 #[derive(Debug)]
@@ -56,9 +53,11 @@ enum Card {
     A = 14,
 }
 
-impl From<char> for Card {
-    fn from(value: char) -> Self {
-        match value {
+impl TryFrom<char> for Card {
+    type Error = InvalidCard;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        Ok(match value {
             '2' => Card::C2,
             '3' => Card::C3,
             '4' => Card::C4,
@@ -72,8 +71,8 @@ impl From<char> for Card {
             'Q' => Card::Q,
             'K' => Card::K,
             'A' => Card::A,
-            _ => panic!("Invalid card character: {}", value),
-        }
+            _ => return Err(InvalidCard(value)),
+        })
     }
 }
 
@@ -93,12 +92,14 @@ struct Hand {
     hand_type: HandType,
 }
 
-impl Hand {
-    pub fn new(input_str: &str) -> Self {
-        let cards: Vec<Card> = input_str.chars().map(|c| Card::from(c)).collect();
+impl FromStr for Hand {
+    type Err = ParseHandError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cards: Vec<Card> = parse_cards(s)?;
         let hand_type = HandType::new(&cards);
 
-        Hand { cards, hand_type }
+        Ok(Hand { cards, hand_type })
     }
 }
 
@@ -167,9 +168,9 @@ impl HandType {
         let cards: Vec<Card> = stack.to_owned();
 
         let sorted = HandType::sort_cards(&cards);
-        println!("Sorted '{stack:?}':");
+        trace!("Sorted '{stack:?}':");
         for (c, f) in &sorted {
-            println!("\t{c:?}: {f}");
+            trace!("\t{c:?}: {f}");
         }
 
         // Check FiveOfKind
@@ -375,7 +376,7 @@ mod tests {
         ]);
 
         for (s, h) in input {
-            let hand = Hand::new(s);
+            let hand = s.parse::<Hand>().unwrap();
 
             assert_eq!(hand.hand_type, h);
         }
@@ -1,4 +1,5 @@
 use itertools::Itertools;
+use tools::{debug, trace};
 
 use super::Address;
 
@@ -17,16 +18,24 @@ impl StepChecker {
         Self { map }
     }
 
+    /// Steps at which `worker` was on an end node, as checked so far.
+    pub fn steps(&self, worker: &Address) -> &[usize] {
+        &self.map[worker]
+    }
+
+    /// Restores the steps checked for `worker`, when resuming from a checkpoint.
+    pub fn restore(&mut self, worker: &Address, steps: Vec<usize>) {
+        self.map.insert(worker.clone(), steps);
+    }
+
     pub fn check(&mut self, worker: &Address, steps: usize) -> bool {
         // Update this worker
         self.map.get_mut(worker).unwrap().push(steps);
 
-        // println!("New Val = #{worker} <= {steps}");
         for (k, v) in &self.map {
-            // println!("#{k} = {:?}", v);
             let deltas: Vec<usize> = v.windows(2).map(|w| w[1] - w[0]).unique().collect();
             if deltas.len() > 0 {
-                println!("#{k} Deltas = {:?}", deltas);
+                trace!("#{k} Deltas = {:?}", deltas);
                 let cycle = deltas[0];
             }
         }
@@ -34,7 +43,7 @@ impl StepChecker {
         // Check is latest value exists in all worker values
         if self.map.values().all(|vec| vec.contains(&steps)) {
             // ALL WORKERS HAVE THIS STEPS VALUE!!
-            println!("Complete match found @ {steps}!!");
+            debug!("Complete match found @ {steps}!!");
             return true;
         }
 
@@ -44,11 +53,9 @@ impl StepChecker {
         // let worker_vals = self.map.get(&worker).unwrap();
         // if worker_vals.len() > 1 {
         //     let prev_steps_val = worker_vals[worker_vals.len() - 2];
-        //     // println!("Deleting all values between ({prev_steps_val} - {steps})");
         //     let exclusion_range = (prev_steps_val + 1..steps);
         //     for (k, v) in self.map.iter_mut() {
         //         v.retain(|e| !exclusion_range.contains(e));
-        //         // println!("#{k} = {v:?}");
         //     }
         // }
 
@@ -70,9 +77,9 @@ impl StepChecker {
 
         #[cfg(debug)]
         if removed {
-            println!("Removed values:");
+            debug!("Removed values:");
             for (k, v) in &self.map {
-                println!("#{k} = {v:?}");
+                debug!("#{k} = {v:?}");
             }
         }
 
@@ -87,7 +94,7 @@ mod tests {
 
     #[test]
     pub fn simple() {
-        let ws = vec!["AAA".into(), "BBB".into(), "CCC".into()];
+        let ws: Vec<Address> = ["AAA", "BBB", "CCC"].map(|s| s.parse().unwrap()).into();
         let mut checker = StepChecker::new(&ws);
 
         for i in 0..10 {
@@ -109,6 +116,6 @@ mod tests {
 
         let lcm = cycles.iter().skip(1).fold(cycles[0], |acc, b| num_integer::lcm(acc, *b));
 
-        println!("LCM = {lcm}");
+        debug!("LCM = {lcm}");
     }
 }
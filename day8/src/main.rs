@@ -1,94 +1,186 @@
 use std::{
-    collections::{HashMap, VecDeque},
+    collections::HashMap,
     fmt::{Debug, Display},
-    sync::{
-        mpsc::{},
-        
-    },
-    thread::{self, current},
-    time::{Instant},
+    str::FromStr,
 };
 
-use crossbeam_queue::SegQueue;
-use humansize::{format_size, DECIMAL};
-use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
-use tools::Opt;
+use serde::{Deserialize, Serialize};
+use tools::checkpoint::Checkpoint;
+use tools::progress::Progress;
+use tools::{debug, input, trace, AocParse, Error, Solution};
 
 use crate::checker::StepChecker;
 
-fn main() {
-    let opt = Opt::load();
-
-    let input = opt.input();
-    let lines: Vec<&str> = input.lines().collect();
+fn main() -> tools::Result<()> {
+    tools::run::<Day8>(&tools::Opt::load())
+}
 
-    let instructions: Vec<Instruction> = lines[0]
-        .chars()
-        .map(|c| match c {
-            'R' => Instruction::Right,
-            'L' => Instruction::Left,
-            _ => panic!("Invalid instruction character: {c}"),
-        })
-        .collect();
+struct Day8 {
+    instructions: Vec<Instruction>,
+    nodes: Vec<Node>,
+    node_map: HashMap<Address, Node>,
+}
 
-    let nodes: Vec<Node> = lines[2..].iter().map(|line| Node::new(line)).collect();
+impl Solution<'_> for Day8 {
+    type Part1 = usize;
+    type Part2 = usize;
 
-    let node_map = {
-        let mut map = HashMap::<Address, Node>::new();
-        for node in &nodes {
-            map.insert(node.address.clone(), node.clone());
+    fn parse(input: &str) -> tools::Result<Self> {
+        let lines: Vec<&str> = input::lines(input).collect();
+        if lines.len() < 3 {
+            return Err(Error::validation("expected instructions, a blank line, then nodes"));
+        }
+        if lines[0].is_empty() {
+            return Err(Error::parse_at(1, 1, "no instructions"));
         }
-        map
-    };
 
-    let start_nodes: Vec<Address> = nodes
-        .iter()
-        .filter(|n| n.address.0[2] == 'A')
-        .map(|n| n.address.clone())
-        .collect();
-    println!("Starting Nodes ({}) = {:?}", start_nodes.len(), start_nodes);
+        let instructions: Vec<Instruction> = lines[0]
+            .chars()
+            .enumerate()
+            .map(|(i, c)| match c {
+                'R' => Ok(Instruction::Right),
+                'L' => Ok(Instruction::Left),
+                _ => Err(Error::parse_at(1, i + 1, format!("invalid instruction character: {c}"))),
+            })
+            .collect::<tools::Result<_>>()?;
+
+        let nodes: Vec<Node> = lines[2..]
+            .iter()
+            .enumerate()
+            .map(|(i, line)| line.parse::<Node>().map_err(|e| e.at_line(i + 3)))
+            .collect::<tools::Result<_>>()?;
+
+        let node_map = {
+            let mut map = HashMap::<Address, Node>::new();
+            for node in &nodes {
+                map.insert(node.address.clone(), node.clone());
+            }
+            map
+        };
 
-    let start_time = Instant::now();
+        Ok(Self {
+            instructions,
+            nodes,
+            node_map,
+        })
+    }
 
-    let mut workers = Vec::<worker::Worker>::new();
+    /// Single walker from `AAA` to `ZZZ`.
+    fn part1(&self) -> tools::Result<usize> {
+        part1::steps_to_end(&self.instructions, &self.node_map)
+    }
 
-    for n in &start_nodes {
-        let worker = worker::Worker::start(n.clone(), node_map.clone(), instructions.clone());
-        workers.push(worker);
+    /// Walkers from every `..A` node at once, until they are all on `..Z` nodes.
+    fn part2(&self) -> tools::Result<usize> {
+        let queue_size = tools::param_or("queue_size", 2000)?;
+        if queue_size == 0 {
+            return Err(Error::validation("queue_size must be at least 1"));
+        }
+        self.walk_all(queue_size)
     }
+}
 
-    let mut checker = StepChecker::new(&start_nodes);
+impl Day8 {
+    /// Runs a worker per walker, each queueing up to `queue_size` ends for the checker.
+    fn walk_all(&self, queue_size: usize) -> tools::Result<usize> {
+        let start_nodes: Vec<Address> = self
+            .nodes
+            .iter()
+            .filter(|n| n.address.0[2] == 'A')
+            .map(|n| n.address.clone())
+            .collect();
+        debug!("Starting Nodes ({}) = {:?}", start_nodes.len(), start_nodes);
+
+        // Resume each walker from the last end the checker saw, with the steps it checked
+        let mut checkpoint = Checkpoint::<Vec<WalkerState>>::new("part2");
+        let resumed = checkpoint.resume()?.unwrap_or_default();
+        let resumed_from = |start: &Address| resumed.iter().find(|w| w.start == start.to_string());
+
+        let mut checker = StepChecker::new(&start_nodes);
+        let mut workers = Vec::<worker::Worker>::new();
+
+        for n in &start_nodes {
+            let from = match resumed_from(n) {
+                Some(state) => {
+                    checker.restore(n, state.steps.clone());
+                    Some(EndResult {
+                        step_count: state.steps.last().copied().unwrap_or(0),
+                        address: state.address.parse()?,
+                    })
+                }
+                None => None,
+            };
+            let worker = worker::Worker::start(
+                n.clone(),
+                from,
+                self.node_map.clone(),
+                self.instructions.clone(),
+                queue_size,
+            );
+            workers.push(worker);
+        }
 
-    let mut checker_bw_ctr = BandwidthCounter::start_new("checker");
-    // 'outer: loop {
-        'outer: for _ in 0..10 {
-        let mut step = 0;
-        for w in &workers {
-            let next = w.next();
-            step = next;
-            if checker.check(&w.start, next) {
-                println!("Eureka!! @ step {}", next);
-                break 'outer;
+        // Last end taken from each worker's queue, which it continues from when resumed
+        let mut last_ends: HashMap<Address, Address> = resumed
+            .iter()
+            .map(|w| Ok((w.start.parse()?, w.address.parse()?)))
+            .collect::<tools::Result<_>>()?;
+
+        let mut progress = Progress::new("checker");
+        let found = 'outer: loop {
+            let mut step = 0;
+            for w in &workers {
+                let next = match w.next() {
+                    Ok(next) => next,
+                    Err(e) => break 'outer Err(e),
+                };
+                step = next.step_count;
+                last_ends.insert(w.start.clone(), next.address);
+                if checker.check(&w.start, next.step_count) {
+                    debug!("Eureka!! @ step {}", next.step_count);
+                    break 'outer Ok(next.step_count);
+                }
             }
+            if progress.tick() {
+                for w in &workers {
+                    trace!("#{}: {} in queue", w.start, w.end_queue.len());
+                }
+                debug!("Step @ {step}");
+            }
+            let saved = checkpoint.tick(|| {
+                last_ends
+                    .iter()
+                    .map(|(start, address)| WalkerState {
+                        start: start.to_string(),
+                        address: address.to_string(),
+                        steps: checker.steps(start).to_vec(),
+                    })
+                    .collect()
+            });
+            if let Err(e) = saved {
+                break 'outer Err(e);
+            }
+        };
+
+        for w in workers {
+            w.stop();
         }
-        if checker_bw_ctr.check() {
-            // for w in &workers {
-            //     println!("#{}: {} in queue", w.start, w.end_queue.len());
-            // }
-            println!("Step @ {step}");
-        }
-    }
 
-    for w in &workers {
-        w.stop();
+        if found.is_ok() {
+            checkpoint.finish()?;
+        }
+        found
     }
+}
 
-    let end_time = Instant::now();
-
-    // println!(
-    //     "Done in {step_ctr} steps (took {:?})",
-    //     (end_time - start_time)
-    // );
+/// Checkpointed progress of one walker in part 2.
+#[derive(Serialize, Deserialize)]
+struct WalkerState {
+    start: String,
+    /// End node the walker was last on.
+    address: String,
+    /// Steps at which the walker was on an end node.
+    steps: Vec<usize>,
 }
 
 struct EndResult {
@@ -121,32 +213,14 @@ impl Display for Instruction {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, AocParse)]
+#[aoc(format = "{address} = ({left}, {right})")]
 struct Node {
     address: Address,
     right: Address,
     left: Address,
 }
 
-impl Node {
-    pub fn new(line: &str) -> Self {
-        let parts: Vec<&str> = line
-            .split([' ', '=', '(', ')', ','])
-            .filter(|s| !s.is_empty())
-            .collect();
-
-        let address = Address::new(parts[0]);
-        let left = Address::new(parts[1]);
-        let right = Address::new(parts[2]);
-
-        Node {
-            address,
-            left,
-            right,
-        }
-    }
-}
-
 impl Display for Node {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}:({}, {})", self.address, self.left, self.right)
@@ -162,18 +236,45 @@ impl Debug for Node {
 #[derive(Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
 struct Address([char; 3]);
 
-impl Address {
-    pub fn new(addr_str: &str) -> Self {
-        assert!(addr_str.len() == 3);
-        let chars: Vec<char> = addr_str.chars().collect();
+/// Why text is not an address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ParseAddressError {
+    /// Number of characters, when not 3.
+    WrongLength(usize),
+    /// A character other than a letter or digit.
+    InvalidChar(char),
+}
+
+impl Display for ParseAddressError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseAddressError::WrongLength(len) => {
+                write!(f, "expected 3 characters, found {len}")
+            }
+            ParseAddressError::InvalidChar(c) => write!(f, "invalid character {c:?}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseAddressError {}
 
-        Address([chars[0], chars[1], chars[2]])
+impl From<ParseAddressError> for Error {
+    fn from(e: ParseAddressError) -> Self {
+        Error::validation(e.to_string())
     }
 }
 
-impl From<&str> for Address {
-    fn from(value: &str) -> Self {
-        Address::new(value)
+impl FromStr for Address {
+    type Err = ParseAddressError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(c) = s.chars().find(|c| !c.is_ascii_alphanumeric()) {
+            return Err(ParseAddressError::InvalidChar(c));
+        }
+        match s.as_bytes() {
+            &[a, b, c] => Ok(Address([a as char, b as char, c as char])),
+            _ => Err(ParseAddressError::WrongLength(s.len())),
+        }
     }
 }
 
@@ -189,38 +290,72 @@ impl Debug for Address {
     }
 }
 
-struct BandwidthCounter {
-    name: String,
-    last_time: Instant,
-    last_count: usize,
-}
+mod part1;
 
-impl BandwidthCounter {
-    pub fn start_new(name: &str) -> Self {
-        Self {
-            name: name.to_owned(),
-            last_time: Instant::now(),
-            last_count: 0usize,
-        }
-    }
+mod worker;
+
+mod checker;
 
-    pub fn check(&mut self) -> bool {
-        self.last_count += 1;
-        let now = Instant::now();
-        let elapsed = now - self.last_time;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        if elapsed.as_secs() > 5 {
-            let bw = (self.last_count as f64 / elapsed.as_secs_f64()) as usize;
-            let f = format_size(bw, DECIMAL);
-            println!("{}: {f} step/sec", self.name);
-            self.last_count = 0;
-            self.last_time = now;
-            return true;
+    #[test]
+    pub fn examples() {
+        let day = Day8::parse(include_str!("../example.txt")).unwrap();
+        assert_eq!(day.part1().unwrap(), 2);
+        let day = Day8::parse(include_str!("../example2.txt")).unwrap();
+        assert_eq!(day.part1().unwrap(), 6);
+    }
+
+    #[test]
+    pub fn no_instructions() {
+        let e = Day8::parse("\n\nAAA = (ZZZ, ZZZ)\n").err().unwrap();
+        match e {
+            Error::Parse(d) => {
+                assert_eq!((d.line, d.column), (1, Some(1)));
+                assert_eq!(d.message, "no instructions");
+            }
+            e => panic!("not a parse error: {e}"),
         }
-        false
     }
-}
 
-mod worker;
+    #[test]
+    pub fn small_queue() {
+        let example = "LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)
+";
+        let day = Day8::parse(example).unwrap();
+        // Workers wait for room instead of dropping ends when their queue is full
+        for queue_size in [1, 2, 2000] {
+            assert_eq!(day.walk_all(queue_size).unwrap(), 6);
+        }
+    }
 
-mod checker;
+    #[test]
+    pub fn addresses() {
+        let address: Address = "11A".parse().unwrap();
+        assert_eq!(address.to_string(), "11A");
+
+        assert_eq!(
+            "AAAA".parse::<Address>(),
+            Err(ParseAddressError::WrongLength(4))
+        );
+        assert_eq!(
+            "A-A".parse::<Address>(),
+            Err(ParseAddressError::InvalidChar('-'))
+        );
+
+        let e = "AAA = (BBB, CC)".parse::<Node>().err().unwrap().to_string();
+        let expected = "invalid right: expected 3 characters, found 2";
+        assert!(e.contains(expected), "{e}");
+    }
+}
@@ -1,4 +1,5 @@
-use super::BandwidthCounter;
+use tools::cancel::{self, CancelToken};
+use tools::progress::Progress;
 
 use std::sync::mpsc::Receiver;
 
@@ -16,7 +17,6 @@ use std::collections::HashMap;
 use super::EndResult;
 
 use crossbeam_queue::ArrayQueue;
-use crossbeam_queue::SegQueue;
 
 use std::sync::Arc;
 
@@ -30,43 +30,57 @@ pub struct Worker {
     pub start: Address,
     thread: JoinHandle<()>,
     stop_sender: Sender<()>,
-    pub end_queue: Arc<ArrayQueue<usize>>,
+    cancel: CancelToken,
+    pub end_queue: Arc<ArrayQueue<EndResult>>,
 }
 
 impl Worker {
+    /// Starts walking from `start_node`, or continues from a checkpointed end `from` it.
     pub fn start(
         start_node: Address,
+        from: Option<EndResult>,
         node_map: HashMap<Address, Node>,
         instructions: Vec<Instruction>,
+        queue_size: usize,
     ) -> Self {
         let (tx, rx) = channel();
-        let queue = Arc::new(ArrayQueue::new(2000));
+        let queue = Arc::new(ArrayQueue::new(queue_size));
         let queue_c = queue.clone();
         let start_node_c = start_node.clone();
+        let from = from.unwrap_or(EndResult {
+            step_count: 0,
+            address: start_node.clone(),
+        });
+        let cancel = cancel::token();
+        let cancel_c = cancel.clone();
 
         let t = thread::spawn(move || {
-            Self::run(node_map, instructions, start_node_c, queue_c, rx);
+            Self::run(node_map, instructions, start_node_c, from, queue_c, rx, cancel_c);
         });
 
         Self {
             start: start_node,
             thread: t,
             stop_sender: tx,
+            cancel,
             end_queue: queue,
         }
     }
 
-    /// Stops this worker thread.
-    pub fn stop(&self) {
-        self.stop_sender.send(()).unwrap();
+    /// Stops this worker thread, and waits for it to finish.
+    pub fn stop(self) {
+        // The thread may already have stopped on cancellation
+        let _ = self.stop_sender.send(());
+        self.thread.join().expect("worker thread panicked");
     }
 
-    /// Get next result
-    pub fn next(&self) -> usize {
+    /// Get next result, or an error if the part was cancelled while waiting for one.
+    pub fn next(&self) -> tools::Result<EndResult> {
         loop {
             if let Some(r) = self.end_queue.pop() {
-                return r;
+                return Ok(r);
             }
+            self.cancel.check()?;
             thread::sleep(Duration::from_millis(10));
         }
     }
@@ -75,48 +89,64 @@ impl Worker {
         node_map: HashMap<Address, Node>,
         instructions: Vec<Instruction>,
         start: Address,
-        end_queue: Arc<ArrayQueue<usize>>,
+        from: EndResult,
+        end_queue: Arc<ArrayQueue<EndResult>>,
         stop_recv: Receiver<()>,
+        cancel: CancelToken,
     ) {
-        let mut current_node = &start;
-        let mut step_ctr = 0usize;
-        let mut input_bw_ctr = BandwidthCounter::start_new(&format!("Input #{start}"));
-        let mut output_bw_ctr = BandwidthCounter::start_new(&format!("Output #{start}"));
-        loop {
-            for instruction in &instructions {
-                let mut z_found = false;
-                match instruction {
-                    Instruction::Right => {
-                        current_node = &node_map[current_node].right;
-                        if current_node.0[2] == 'Z' {
-                            z_found = true;
-                        }
+        let _span = tools::profile::span(&format!("worker {start}"));
+        if instructions.is_empty() {
+            return;
+        }
+        let mut current_node = &from.address;
+        let progress = Progress::new(&format!("Worker #{start}"));
+        let mut input_progress = progress.child("steps");
+        let mut output_progress = progress.child("ends");
+        // Continue at the instruction after the checkpointed end
+        let offset = from.step_count % instructions.len();
+        let stopped = || stop_recv.try_recv().is_ok() || cancel.is_cancelled();
+        let steps = instructions.iter().cycle().skip(offset);
+        for (step_ctr, instruction) in (from.step_count + 1..).zip(steps) {
+            let mut z_found = false;
+            match instruction {
+                Instruction::Right => {
+                    current_node = &node_map[current_node].right;
+                    if current_node.0[2] == 'Z' {
+                        z_found = true;
                     }
-                    Instruction::Left => {
-                        current_node = &node_map[current_node].left;
-                        if current_node.0[2] == 'Z' {
-                            z_found = true;
-                        }
+                }
+                Instruction::Left => {
+                    current_node = &node_map[current_node].left;
+                    if current_node.0[2] == 'Z' {
+                        z_found = true;
                     }
                 }
-                step_ctr += 1;
-
-                if z_found {
-                    // println!("[{step_ctr}] = ({z_count}) ### {:?} ###", current_nodes);
-                    end_queue.push(step_ctr);
-                    output_bw_ctr.check();
+            }
+            if z_found {
+                let mut end = EndResult {
+                    step_count: step_ctr,
+                    address: current_node.clone(),
+                };
+                // Wait for the checker to take results when full, as a lost end is a wrong answer
+                while let Err(rejected) = end_queue.push(end) {
+                    if stopped() {
+                        return;
+                    }
+                    thread::sleep(Duration::from_millis(1));
+                    end = rejected;
                 }
+                output_progress.tick();
+            }
 
-                input_bw_ctr.check();
+            input_progress.tick();
 
-                if let Ok(()) = stop_recv.try_recv() {
-                    return;
-                }
+            if stopped() {
+                return;
+            }
 
-                // Try to release CPU if working too fast
-                if end_queue.len() > 1000 {
-                    thread::sleep(Duration::from_millis(1));
-                }
+            // Try to release CPU if working too fast
+            if end_queue.len() > end_queue.capacity() / 2 {
+                thread::sleep(Duration::from_millis(1));
             }
         }
     }
@@ -1,50 +1,72 @@
 use std::collections::{BTreeMap, VecDeque};
 
-use tools::Opt;
+use tools::{debug, input, trace, Solution};
 
-fn main() {
-    let opt = Opt::load();
-    let input = opt.input();
-    let lines: Vec<&str> = input.lines().collect();
+fn main() -> tools::Result<()> {
+    tools::run::<Day9>(&tools::Opt::load())
+}
 
-    let mut acc_score = 0;
-    for line in &lines {
-        let mut seq = Sequence::new(line);
-        seq.print();
+struct Day9 {
+    sequences: Vec<Sequence>,
+}
 
-        while let Some(idx) = seq.calc_next_level() {
-            // seq.print();
-        }
+impl Solution<'_> for Day9 {
+    type Part1 = i32;
+    type Part2 = i32;
 
-        let res = if opt.is_part1 {
-            seq.calc_next_val()
-        } else {
-            seq.calc_prev_val()
-        };
+    fn parse(input: &str) -> tools::Result<Self> {
+        let sequences = input::lines(input)
+            .enumerate()
+            .map(|(i, line)| Sequence::new(line).map_err(|e| e.at_line(i + 1)))
+            .collect::<tools::Result<Vec<_>>>()?;
+
+        Ok(Self { sequences })
+    }
 
-        println!("New Value: {res}");
-        seq.print();
-        acc_score += res;
+    fn part1(&self) -> tools::Result<i32> {
+        Ok(self.extrapolate(Sequence::calc_next_val))
     }
 
-    println!("Total score = {acc_score}");
+    fn part2(&self) -> tools::Result<i32> {
+        Ok(self.extrapolate(Sequence::calc_prev_val))
+    }
 }
-#[derive(Debug)]
+
+impl Day9 {
+    /// Extrapolates every sequence with `calc`, and sums the new values.
+    fn extrapolate(&self, calc: fn(&mut Sequence) -> i32) -> i32 {
+        let mut acc_score = 0;
+        for seq in &self.sequences {
+            let mut seq = seq.clone();
+            seq.print();
+
+            while let Some(idx) = seq.calc_next_level() {
+                // seq.print();
+            }
+
+            let res = calc(&mut seq);
+
+            debug!("New Value: {res}");
+            seq.print();
+            acc_score += res;
+        }
+        acc_score
+    }
+}
+
+#[derive(Debug, Clone)]
 struct Sequence {
     map: BTreeMap<usize, Vec<i32>>,
 }
 
 impl Sequence {
-    pub fn new(input: &str) -> Self {
-        let top = input
-            .split(" ")
-            .map(|s| s.parse::<i32>().unwrap())
-            .collect();
+    pub fn new(line: &str) -> tools::Result<Self> {
+        let top = input::ints::<i32>(line)?;
 
         let mut map = BTreeMap::new();
         map.insert(0, top);
 
-        Self { map }
+        Ok(Self { map })
     }
 
     pub fn calc_next_level(&mut self) -> Option<usize> {
@@ -141,10 +163,21 @@ impl Sequence {
     }
 
     pub fn print(&self) {
-        println!("Sequence:");
+        trace!("Sequence:");
         for (k, v) in &self.map {
-            println!("[{k}] = {v:?}");
+            trace!("[{k}] = {v:?}");
         }
-        println!("");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn example() {
+        let day = Day9::parse(include_str!("../example.txt")).unwrap();
+        assert_eq!(day.part1().unwrap(), 114);
+        assert_eq!(day.part2().unwrap(), 2);
     }
 }
@@ -1,3 +1,4 @@
+#[derive(Debug, Clone)]
 pub struct Arena<T> {
     data: Vec<Vec<T>>,
     width: usize,
@@ -66,7 +67,8 @@ where T: Clone {
         }
     }
 
-    pub fn print<F>(&self, f: F) where F: Fn(&Position, &T) -> char {
+    /// Draws the arena with one character per tile from `f`, a line per row.
+    pub fn render<F>(&self, f: F) -> String where F: Fn(&Position, &T) -> char {
         let mut s = String::new();
         for y in 0..self.height {
             for x in 0..self.width {
@@ -76,7 +78,7 @@ where T: Clone {
             }
             s.push('\n');
         }
-        println!("{}", s);
+        s
     }
 }
 
@@ -0,0 +1,103 @@
+use std::fmt::Display;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use crate::Result;
+
+/// Summary of repeated timings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub runs: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    pub stddev: Duration,
+}
+
+impl Stats {
+    /// Summarizes a non-empty list of timings.
+    pub fn new(mut samples: Vec<Duration>) -> Self {
+        assert!(!samples.is_empty(), "no samples to summarize");
+        samples.sort();
+
+        let runs = samples.len();
+        let median = if runs % 2 == 1 {
+            samples[runs / 2]
+        } else {
+            (samples[runs / 2 - 1] + samples[runs / 2]) / 2
+        };
+
+        let secs: Vec<f64> = samples.iter().map(Duration::as_secs_f64).collect();
+        let mean = secs.iter().sum::<f64>() / runs as f64;
+        let variance = secs.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / runs as f64;
+
+        Stats {
+            runs,
+            min: samples[0],
+            median,
+            mean: Duration::from_secs_f64(mean),
+            stddev: Duration::from_secs_f64(variance.sqrt()),
+        }
+    }
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "min {:?}, median {:?}, mean {:?}, stddev {:?} ({} runs)",
+            self.min, self.median, self.mean, self.stddev, self.runs
+        )
+    }
+}
+
+/// Runs `f` once to warm up, then `runs` more times, timing each run.
+/// Returns the warm-up result along with the timings.
+pub fn measure<T, F>(runs: usize, mut f: F) -> Result<(T, Stats)>
+where
+    F: FnMut() -> Result<T>,
+{
+    let result = f()?;
+
+    let mut samples = Vec::with_capacity(runs);
+    for _ in 0..runs {
+        let start = Instant::now();
+        black_box(f()?);
+        samples.push(start.elapsed());
+    }
+
+    Ok((result, Stats::new(samples)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn stats() {
+        let ms = |n| Duration::from_millis(n);
+        let stats = Stats::new(vec![ms(4), ms(2), ms(6), ms(4)]);
+
+        assert_eq!(stats.runs, 4);
+        assert_eq!(stats.min, ms(2));
+        assert_eq!(stats.median, ms(4));
+        assert_eq!(stats.mean, ms(4));
+        assert!(stats.stddev.abs_diff(Duration::from_micros(1414)) < Duration::from_micros(1));
+
+        assert_eq!(Stats::new(vec![ms(3), ms(1), ms(2)]).median, ms(2));
+    }
+
+    #[test]
+    pub fn measure_repeats() {
+        let mut calls = 0;
+        let (first, stats) = measure(3, || {
+            calls += 1;
+            Ok(calls)
+        })
+        .unwrap();
+
+        assert_eq!(first, 1);
+        assert_eq!(calls, 4);
+        assert_eq!(stats.runs, 3);
+    }
+}
@@ -0,0 +1,106 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use structopt::StructOpt;
+use tools::client::{self, Client};
+use tools::shard::{self, Merge};
+use tools::submit::{self, History};
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "aoc", about = "Advent of Code 2023 - puzzle website helpers")]
+enum Command {
+    /// Download a day's input to dayN/input.txt, unless it's already there
+    Fetch {
+        /// Day to fetch
+        day: u8,
+
+        #[structopt(long = "year", default_value = "2023")]
+        year: u16,
+
+        /// Website to fetch from, e.g. a local stub server for testing
+        #[structopt(long = "base-url", env = "AOC_BASE_URL", default_value = client::DEFAULT_BASE_URL)]
+        base_url: String,
+    },
+    /// Submit an answer, unless it's already known to be wrong, and record the response
+    Submit {
+        /// Day of the puzzle
+        day: u8,
+
+        /// Part the answer is for, 1 or 2
+        part: u8,
+
+        answer: String,
+
+        #[structopt(long = "year", default_value = "2023")]
+        year: u16,
+
+        /// Website to submit to, e.g. a local stub server for testing
+        #[structopt(long = "base-url", env = "AOC_BASE_URL", default_value = client::DEFAULT_BASE_URL)]
+        base_url: String,
+    },
+    /// Combine the `--format json` answers of every `--shard` run into the final answers
+    Merge {
+        /// How to combine the shards' answers: sum, max or min
+        merge: Merge,
+
+        /// Files with the shards' answers, or stdin if none
+        #[structopt(parse(from_os_str))]
+        files: Vec<PathBuf>,
+    },
+}
+
+fn main() -> tools::Result<()> {
+    match Command::from_args() {
+        Command::Fetch {
+            day,
+            year,
+            base_url,
+        } => {
+            let (path, downloaded) = client::fetch_cached(&tools::day_dir(day), || {
+                Client::from_env(&base_url, year)?.fetch_input(day)
+            })?;
+            if downloaded {
+                println!("Saved day {day} input to {}", path.display());
+            } else {
+                println!("Day {day} input is already in {}", path.display());
+            }
+        }
+        Command::Submit {
+            day,
+            part,
+            answer,
+            year,
+            base_url,
+        } => {
+            if !(1..=2).contains(&part) {
+                return Err(tools::Error::Usage(format!(
+                    "invalid part {part}, expected 1 or 2"
+                )));
+            }
+            let client = Client::from_env(&base_url, year)?;
+            let mut history = History::load(&History::file_for(&tools::day_dir(day)))?;
+            let outcome = submit::submit(&client, &mut history, day, part, &answer)?;
+            println!("Day {day} part {part}: {answer} is {outcome}");
+        }
+        Command::Merge { merge, files } => {
+            let mut partials = vec![];
+            if files.is_empty() {
+                let mut text = String::new();
+                std::io::stdin()
+                    .read_to_string(&mut text)
+                    .map_err(|e| tools::Error::io(Path::new("-"), e))?;
+                partials = shard::read_partials(&text)?;
+            }
+            for file in &files {
+                let text = std::fs::read_to_string(file).map_err(|e| tools::Error::io(file, e))?;
+                partials.extend(shard::read_partials(&text).map_err(|e| e.with_path(file))?);
+            }
+
+            for (part, answer) in shard::merge(merge, &partials)? {
+                println!("Part {part}: {answer}");
+            }
+        }
+    }
+
+    Ok(())
+}
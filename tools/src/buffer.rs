@@ -0,0 +1,195 @@
+//! Puzzle input loaded once and shared, so parsing can borrow from it.
+//!
+//! [`Opt::buffer`](crate::Opt::buffer) reads the input on first use and keeps it for the
+//! rest of the run. With the `mmap` feature, input files are memory-mapped instead of read,
+//! e.g. `cargo run --release --features tools/mmap`.
+
+use std::fmt::Debug;
+use std::io::Read;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+use crate::input;
+use crate::{Error, Result};
+
+/// The whole input text.
+pub struct Buffer {
+    path: PathBuf,
+    data: Data,
+}
+
+enum Data {
+    Owned(String),
+    #[cfg(all(unix, feature = "mmap"))]
+    Mapped(mmap::Map),
+}
+
+impl Buffer {
+    /// Loads the file at `path`, where `-` means stdin.
+    pub fn load(path: &Path) -> Result<Self> {
+        if path == Path::new("-") {
+            let mut text = String::new();
+            std::io::stdin()
+                .read_to_string(&mut text)
+                .map_err(|e| Error::io(path, e))?;
+            return Ok(Self::from_string(path, text));
+        }
+
+        #[cfg(all(unix, feature = "mmap"))]
+        if let Some(map) = mmap::Map::open(path)? {
+            // Checked here to report invalid input as an error, not a panic in `as_str`
+            std::str::from_utf8(map.bytes()).map_err(|e| {
+                let invalid = std::io::Error::new(std::io::ErrorKind::InvalidData, e);
+                Error::io(path, invalid)
+            })?;
+            return Ok(Self {
+                path: path.to_owned(),
+                data: Data::Mapped(map),
+            });
+        }
+
+        let text = std::fs::read_to_string(path).map_err(|e| Error::io(path, e))?;
+        Ok(Self::from_string(path, text))
+    }
+
+    /// Wraps text already in memory, e.g. for tests.
+    pub fn from_string(path: &Path, text: String) -> Self {
+        Self {
+            path: path.to_owned(),
+            data: Data::Owned(text),
+        }
+    }
+
+    /// Gets the path the input was loaded from.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn as_str(&self) -> &str {
+        match &self.data {
+            Data::Owned(text) => text,
+            // Checked again, as the file could be changed by another process while mapped
+            #[cfg(all(unix, feature = "mmap"))]
+            Data::Mapped(map) => std::str::from_utf8(map.bytes()).expect("mapped input is UTF-8"),
+        }
+    }
+
+    /// Whether the input is memory-mapped rather than read into memory.
+    pub fn is_mapped(&self) -> bool {
+        !matches!(self.data, Data::Owned(_))
+    }
+
+    /// Iterates over lines, see [`input::lines`].
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        input::lines(self.as_str())
+    }
+}
+
+impl Deref for Buffer {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Debug for Buffer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Buffer")
+            .field("path", &self.path)
+            .field("len", &self.as_str().len())
+            .field("mapped", &self.is_mapped())
+            .finish()
+    }
+}
+
+#[cfg(all(unix, feature = "mmap"))]
+mod mmap {
+    use std::fs::File;
+    use std::os::unix::io::AsRawFd;
+    use std::path::Path;
+
+    use crate::{Error, Result};
+
+    /// A read-only private mapping of a whole file.
+    pub struct Map {
+        ptr: *mut libc::c_void,
+        len: usize,
+    }
+
+    // SAFETY: the mapping is never written, and only unmapped on drop
+    unsafe impl Send for Map {}
+    unsafe impl Sync for Map {}
+
+    impl Map {
+        /// Maps the file at `path`. Returns `None` for empty files, which can't be mapped.
+        pub fn open(path: &Path) -> Result<Option<Self>> {
+            let file = File::open(path).map_err(|e| Error::io(path, e))?;
+            let len = file.metadata().map_err(|e| Error::io(path, e))?.len() as usize;
+            if len == 0 {
+                return Ok(None);
+            }
+
+            // SAFETY: a fresh read-only mapping of an open file, checked for failure below
+            let ptr = unsafe {
+                libc::mmap(
+                    std::ptr::null_mut(),
+                    len,
+                    libc::PROT_READ,
+                    libc::MAP_PRIVATE,
+                    file.as_raw_fd(),
+                    0,
+                )
+            };
+            if ptr == libc::MAP_FAILED {
+                return Err(Error::io(path, std::io::Error::last_os_error()));
+            }
+            Ok(Some(Self { ptr, len }))
+        }
+
+        pub fn bytes(&self) -> &[u8] {
+            // SAFETY: `ptr` points to `len` mapped bytes until this is dropped
+            unsafe { std::slice::from_raw_parts(self.ptr as *const u8, self.len) }
+        }
+    }
+
+    impl Drop for Map {
+        fn drop(&mut self) {
+            // SAFETY: unmaps exactly the mapping made in `open`
+            unsafe {
+                libc::munmap(self.ptr, self.len);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::ByteGrid;
+
+    #[test]
+    pub fn loads_once() {
+        let path = std::env::temp_dir().join(format!("tools-buffer-{}.txt", std::process::id()));
+        std::fs::write(&path, "#.#\r\n..#\r\n\r\nseeds: 1 2\n").unwrap();
+
+        let buffer = Buffer::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(buffer.path(), path);
+        assert_eq!(buffer.is_mapped(), cfg!(all(unix, feature = "mmap")));
+
+        // Lines and blocks borrow from the buffer
+        let first = buffer.lines().next().unwrap();
+        assert_eq!(first, "#.#");
+        assert_eq!(first.as_ptr(), buffer.as_ptr());
+        let blocks: Vec<&str> = input::blocks(&buffer).collect();
+        assert_eq!(blocks, vec!["#.#\r\n..#", "seeds: 1 2"]);
+
+        let grid = ByteGrid::new(blocks[0]).unwrap();
+        assert_eq!(grid.get(2, 1), Some(b'#'));
+        assert_eq!(grid.row(0).as_ptr(), buffer.as_ptr());
+
+        let empty = Buffer::from_string(Path::new("-"), String::new());
+        assert_eq!(empty.lines().count(), 0);
+    }
+}
@@ -0,0 +1,175 @@
+//! Cache of answers from earlier runs, so unchanged code on unchanged input doesn't run again.
+//!
+//! Answers are keyed on the day, part, a hash of the input and params, and an id of the
+//! binary that found them, which changes whenever it's rebuilt. They are kept in `.answers.cache`
+//! in the day's directory, one JSON object per line.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Result};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Entry {
+    day: Option<u8>,
+    part: u8,
+    input: String,
+    build: String,
+    answer: String,
+    /// Whether the answer is partial, from one shard of the work.
+    #[serde(default)]
+    sharded: bool,
+}
+
+/// Answers found for one input by this build.
+pub struct AnswerCache {
+    path: PathBuf,
+    day: Option<u8>,
+    input: String,
+    build: String,
+    entries: Vec<Entry>,
+}
+
+impl AnswerCache {
+    /// Gets the cache file in a day's directory.
+    pub fn file_for(day_dir: &Path) -> PathBuf {
+        day_dir.join(".answers.cache")
+    }
+
+    /// Reads the cached answers for `input` and `params` from this binary.
+    /// Returns `None` if the binary can't be identified, so nothing can be cached.
+    pub fn load(path: &Path, day: Option<u8>, input: &str, params: &str) -> Result<Option<Self>> {
+        let Some(build) = build_id() else {
+            return Ok(None);
+        };
+        let input = format!(
+            "{:016x}",
+            fnv1a(&[input.as_bytes(), b"\0", params.as_bytes()])
+        );
+
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(Error::io(path, e)),
+        };
+        // Entries from other builds or inputs are kept in the file, but not loaded
+        let entries = crate::input::lines(&text)
+            .filter_map(|line| serde_json::from_str::<Entry>(line).ok())
+            .filter(|e| e.day == day && e.input == input && e.build == build)
+            .collect();
+
+        Ok(Some(Self {
+            path: path.to_owned(),
+            day,
+            input,
+            build,
+            entries,
+        }))
+    }
+
+    /// Gets the cached answer for part `n`, and whether it is from one shard of the work.
+    pub fn get(&self, n: u8) -> Option<(&str, bool)> {
+        // The file is only appended to, so the last entry is the latest
+        self.entries
+            .iter()
+            .rev()
+            .find(|e| e.part == n)
+            .map(|e| (e.answer.as_str(), e.sharded))
+    }
+
+    /// Caches the answer for part `n`, replacing any cached one.
+    pub fn insert(&mut self, n: u8, answer: &str, sharded: bool) -> Result<()> {
+        let entry = Entry {
+            day: self.day,
+            part: n,
+            input: self.input.clone(),
+            build: self.build.clone(),
+            answer: answer.to_owned(),
+            sharded,
+        };
+        let line = serde_json::to_string(&entry).expect("cache entry is serializable");
+
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| Error::io(&self.path, e))?;
+        writeln!(file, "{line}").map_err(|e| Error::io(&self.path, e))?;
+
+        self.entries.retain(|e| e.part != n);
+        self.entries.push(entry);
+        Ok(())
+    }
+}
+
+/// Identifies the running binary by its size and modification time, which change on rebuild.
+fn build_id() -> Option<String> {
+    let metadata = std::env::current_exe().ok()?.metadata().ok()?;
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+
+    let mut bytes = metadata.len().to_le_bytes().to_vec();
+    bytes.extend(modified.as_nanos().to_le_bytes());
+    Some(format!("{:016x}", fnv1a(&[&bytes])))
+}
+
+/// 64-bit FNV-1a hash of the concatenated `parts`, stable across runs and platforms.
+pub(crate) fn fnv1a(parts: &[&[u8]]) -> u64 {
+    parts
+        .iter()
+        .flat_map(|p| p.iter())
+        .fold(0xcbf29ce484222325, |hash, b| {
+            (hash ^ *b as u64).wrapping_mul(0x100000001b3)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn hashes() {
+        assert_eq!(fnv1a(&[]), 0xcbf29ce484222325);
+        assert_eq!(fnv1a(&[b"a"]), 0xaf63dc4c8601ec8c);
+        assert_eq!(fnv1a(&[b"ab", b"c"]), fnv1a(&[b"abc"]));
+        assert_eq!(build_id(), build_id());
+    }
+
+    #[test]
+    pub fn keyed_on_input() {
+        let path = std::env::temp_dir().join(format!("tools-cache-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut cache = AnswerCache::load(&path, Some(4), "1 2 3", "")
+            .unwrap()
+            .unwrap();
+        assert_eq!(cache.get(1), None);
+        cache.insert(1, "13", false).unwrap();
+        cache.insert(1, "14", false).unwrap();
+        cache.insert(2, "7", true).unwrap();
+
+        let cache = AnswerCache::load(&path, Some(4), "1 2 3", "")
+            .unwrap()
+            .unwrap();
+        assert_eq!(cache.get(1), Some(("14", false)));
+        assert_eq!(cache.get(2), Some(("7", true)));
+        assert_eq!(cache.get(3), None);
+
+        let other = AnswerCache::load(&path, Some(4), "1 2 4", "")
+            .unwrap()
+            .unwrap();
+        assert_eq!(other.get(1), None);
+        let other = AnswerCache::load(&path, Some(5), "1 2 3", "")
+            .unwrap()
+            .unwrap();
+        assert_eq!(other.get(1), None);
+        let other = AnswerCache::load(&path, Some(4), "1 2 3", "cycles=3")
+            .unwrap()
+            .unwrap();
+        assert_eq!(other.get(1), None);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
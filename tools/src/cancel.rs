@@ -0,0 +1,105 @@
+//! Cooperative cancellation, used to stop a part that runs past `--timeout`.
+//!
+//! Long running loops poll [`check`] (or a [`CancelToken`] handed to worker threads)
+//! and return early with [`Error::Cancelled`] once the part is cancelled.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::{Error, Result};
+
+/// Shared flag telling a solver, and any threads it started, to stop.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+static CURRENT: Mutex<Option<CancelToken>> = Mutex::new(None);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Returns [`Error::Cancelled`] if cancelled, so loops can stop with `?`.
+    pub fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            Err(Error::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Makes `token` the one returned by [`token`], for the part about to be solved.
+pub fn install(token: CancelToken) {
+    *CURRENT.lock().unwrap_or_else(|e| e.into_inner()) = Some(token);
+}
+
+/// Gets the token for the part being solved, to hand to worker threads.
+/// Outside of a run this is a token that is never cancelled.
+pub fn token() -> CancelToken {
+    CURRENT
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .unwrap_or_default()
+}
+
+/// Returns [`Error::Cancelled`] if the part being solved was cancelled.
+pub fn check() -> Result<()> {
+    token().check()
+}
+
+/// Runs `f`, cancelling `token` if it hasn't finished after `limit`.
+/// `f` still has to notice the cancellation and return, this only waits for it.
+pub fn with_deadline<T, F>(limit: Duration, token: &CancelToken, f: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    let (done, finished) = mpsc::channel::<()>();
+    std::thread::scope(|s| {
+        s.spawn(move || {
+            if let Err(RecvTimeoutError::Timeout) = finished.recv_timeout(limit) {
+                token.cancel();
+            }
+        });
+
+        let result = f();
+        let _ = done.send(());
+        result
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn deadline() {
+        let token = CancelToken::new();
+        let answer = with_deadline(Duration::from_secs(60), &token, || 42);
+        assert_eq!(answer, 42);
+        assert!(token.check().is_ok());
+
+        let spins = with_deadline(Duration::from_millis(10), &token, || {
+            let mut spins = 0u64;
+            while token.check().is_ok() {
+                spins += 1;
+            }
+            spins
+        });
+        assert!(spins > 0);
+        assert!(matches!(token.check(), Err(Error::Cancelled)));
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+
+use crate::{Error, Result};
+
+/// Known-good answers for an input, read from its solution file.
+///
+/// Each line is `<part>: <answer>`, blank lines and lines starting with `#` are ignored:
+/// ```text
+/// 1: 13
+/// 2: 30
+/// ```
+pub struct Answers {
+    answers: HashMap<u8, String>,
+}
+
+impl Answers {
+    /// Gets the solution file for an input, `input.txt` answers are in `solution.txt`,
+    /// and other inputs like `example.txt` have theirs in `example.solution.txt`.
+    pub fn file_for(input: &Path) -> PathBuf {
+        let stem = input.file_stem().unwrap_or_default().to_string_lossy();
+        let name = if stem == "input" {
+            "solution.txt".to_owned()
+        } else {
+            format!("{stem}.solution.txt")
+        };
+        input.with_file_name(name)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path).map_err(|e| Error::io(path, e))?;
+        Self::parse(&text).map_err(|e| e.with_path(path))
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut answers = HashMap::new();
+        for (i, line) in crate::input::lines(text).enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let part = line
+                .split_once(':')
+                .and_then(|(part, answer)| Some((part.trim().parse::<u8>().ok()?, answer.trim())));
+            match part {
+                Some((part @ (1 | 2), answer)) => answers.insert(part, answer.to_owned()),
+                _ => {
+                    return Err(Error::parse(
+                        i + 1,
+                        "expected `1: <answer>` or `2: <answer>`",
+                    ))
+                }
+            };
+        }
+
+        Ok(Self { answers })
+    }
+
+    /// Gets the recorded answer for part `n`.
+    pub fn get(&self, n: u8) -> Option<&str> {
+        self.answers.get(&n).map(|a| a.as_str())
+    }
+
+    /// Compares an answer for part `n` with the recorded one.
+    pub fn check(&self, n: u8, actual: &str) -> Verdict {
+        match self.get(n) {
+            Some(expected) if expected == actual => Verdict::Pass,
+            Some(expected) => Verdict::Fail {
+                expected: expected.to_owned(),
+                actual: actual.to_owned(),
+            },
+            None => Verdict::Missing,
+        }
+    }
+}
+
+/// Outcome of checking one answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    Fail {
+        expected: String,
+        actual: String,
+    },
+    /// No answer is recorded for the part, which is not a failure.
+    Missing,
+}
+
+impl Verdict {
+    pub fn is_failure(&self) -> bool {
+        matches!(self, Verdict::Fail { .. })
+    }
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Verdict::Pass => write!(f, "PASS"),
+            Verdict::Fail { expected, actual } => {
+                write!(f, "FAIL, expected {expected}, got {actual}")
+            }
+            Verdict::Missing => write!(f, "no recorded answer"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn solution_file_names() {
+        assert_eq!(
+            Answers::file_for(Path::new("day4/input.txt")),
+            Path::new("day4/solution.txt")
+        );
+        assert_eq!(
+            Answers::file_for(Path::new("example2.txt")),
+            Path::new("example2.solution.txt")
+        );
+    }
+
+    #[test]
+    pub fn parse_answers() {
+        let answers = Answers::parse("# day 4\r\n1: 13\r\n\r\n2:30\r\n").unwrap();
+
+        assert_eq!(answers.get(1), Some("13"));
+        assert_eq!(answers.get(2), Some("30"));
+        assert_eq!(answers.check(1, "13"), Verdict::Pass);
+        assert!(answers.check(2, "31").is_failure());
+        assert_eq!(
+            Answers::parse("1: 13").unwrap().check(2, "30"),
+            Verdict::Missing
+        );
+
+        assert!(Answers::parse("3: 1").is_err());
+        assert!(Answers::parse("13").is_err());
+    }
+}
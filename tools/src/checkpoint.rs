@@ -0,0 +1,208 @@
+//! Checkpoints for long running searches, so they can continue after being stopped.
+//!
+//! A solver saves its state with [`Checkpoint::tick`], which writes it every few seconds to
+//! `.checkpoints/<name>.json` in the day's directory. With `--resume`, [`Checkpoint::resume`]
+//! loads the last saved state, as long as it was saved for the same input and params.
+
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::{info, Error, Result};
+
+/// Where checkpoints of this run are kept, and which input they are for.
+#[derive(Debug, Clone)]
+pub struct Context {
+    dir: PathBuf,
+    key: String,
+    resume: bool,
+}
+
+static CONTEXT: OnceLock<Context> = OnceLock::new();
+
+impl Context {
+    /// Keeps checkpoints in `.checkpoints` in `day_dir`, keyed on `input` and `params`.
+    /// Saved states are only loaded when `resume` is set.
+    pub fn new(day_dir: &Path, input: &str, params: &str, resume: bool) -> Self {
+        let key = crate::cache::fnv1a(&[input.as_bytes(), b"\0", params.as_bytes()]);
+        Self {
+            dir: day_dir.join(".checkpoints"),
+            key: format!("{key:016x}"),
+            resume,
+        }
+    }
+}
+
+/// Enables checkpoints for this run. Only the first call has any effect.
+/// Without it, checkpoints are never written or loaded.
+pub fn init(context: Context) {
+    let _ = CONTEXT.set(context);
+}
+
+#[derive(Serialize, Deserialize)]
+struct File<T> {
+    key: String,
+    state: T,
+}
+
+/// Saved state of one search.
+pub struct Checkpoint<T> {
+    name: String,
+    context: Option<Context>,
+    interval: Duration,
+    last: Instant,
+    state: PhantomData<T>,
+}
+
+impl<T: Serialize + DeserializeOwned> Checkpoint<T> {
+    /// Creates the checkpoint `name`, which must be unique within the day.
+    pub fn new(name: &str) -> Self {
+        Self::with_context(name, CONTEXT.get().cloned())
+    }
+
+    fn with_context(name: &str, context: Option<Context>) -> Self {
+        Self {
+            name: name.to_owned(),
+            context,
+            interval: Duration::from_secs(10),
+            last: Instant::now(),
+            state: PhantomData,
+        }
+    }
+
+    /// Sets how often the state is saved.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    fn path(&self) -> Option<PathBuf> {
+        let context = self.context.as_ref()?;
+        Some(context.dir.join(format!("{}.json", self.name)))
+    }
+
+    /// Loads the last saved state when resuming.
+    /// Returns `None` if not resuming, or nothing was saved for this input.
+    pub fn resume(&self) -> Result<Option<T>> {
+        let Some(context) = self.context.as_ref().filter(|c| c.resume) else {
+            return Ok(None);
+        };
+        let path = self.path().expect("checkpoints are enabled");
+
+        let Some(file) = load(&path)? else {
+            return Ok(None);
+        };
+        if file.key != context.key {
+            info!(
+                "{}: checkpoint is for another input, starting over",
+                self.name
+            );
+            return Ok(None);
+        }
+
+        let state = serde_json::from_value(file.state)
+            .map_err(|e| Error::validation(e.to_string()).with_path(&path))?;
+        info!("{}: resuming from {}", self.name, path.display());
+        Ok(Some(state))
+    }
+
+    /// Saves the state from `state` if it's time to, returning whether it was saved.
+    pub fn tick<F>(&mut self, state: F) -> Result<bool>
+    where
+        F: FnOnce() -> T,
+    {
+        if self.context.is_none() || self.last.elapsed() < self.interval {
+            return Ok(false);
+        }
+        self.save(&state())?;
+        Ok(true)
+    }
+
+    /// Saves the state now.
+    pub fn save(&mut self, state: &T) -> Result<()> {
+        let (Some(context), Some(path)) = (&self.context, self.path()) else {
+            return Ok(());
+        };
+        let file = File {
+            key: context.key.clone(),
+            state,
+        };
+        let json = serde_json::to_string(&file).expect("checkpoint state is serializable");
+
+        // Written next to the checkpoint then renamed, so a kill mid-write keeps the last one
+        std::fs::create_dir_all(&context.dir).map_err(|e| Error::io(&context.dir, e))?;
+        let temp = path.with_extension("json.tmp");
+        std::fs::write(&temp, json).map_err(|e| Error::io(&temp, e))?;
+        std::fs::rename(&temp, &path).map_err(|e| Error::io(&path, e))?;
+
+        self.last = Instant::now();
+        Ok(())
+    }
+
+    /// Removes the saved state once the search is done, unless it is for another input.
+    pub fn finish(self) -> Result<()> {
+        let (Some(context), Some(path)) = (&self.context, self.path()) else {
+            return Ok(());
+        };
+        match load(&path)? {
+            Some(file) if file.key == context.key => {
+                std::fs::remove_file(&path).map_err(|e| Error::io(&path, e))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Reads a checkpoint file, without its state yet. Returns `None` if it doesn't exist.
+fn load(path: &Path) -> Result<Option<File<serde_json::Value>>> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(Error::io(path, e)),
+    };
+    serde_json::from_str(&text)
+        .map(Some)
+        .map_err(|e| Error::parse(e.line(), e.to_string()).with_path(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn save_and_resume() {
+        let dir = std::env::temp_dir().join(format!("tools-checkpoint-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let context = |input, resume| Some(Context::new(&dir, input, "", resume));
+
+        let mut checkpoint =
+            Checkpoint::<(usize, u64)>::with_context("seeds", context("1 2 3", false))
+                .interval(Duration::ZERO);
+        assert!(checkpoint.tick(|| (2, 35)).unwrap());
+
+        // Only loaded when resuming, for the same input
+        let resumed = Checkpoint::<(usize, u64)>::with_context("seeds", context("1 2 3", true));
+        assert_eq!(resumed.resume().unwrap(), Some((2, 35)));
+        let other = Checkpoint::<(usize, u64)>::with_context("seeds", context("1 2 4", true));
+        assert_eq!(other.resume().unwrap(), None);
+        assert_eq!(checkpoint.resume().unwrap(), None);
+
+        // Finishing another input's search keeps this one
+        other.finish().unwrap();
+        assert_eq!(resumed.resume().unwrap(), Some((2, 35)));
+        resumed.finish().unwrap();
+        let resumed = Checkpoint::<(usize, u64)>::with_context("seeds", context("1 2 3", true));
+        assert_eq!(resumed.resume().unwrap(), None);
+
+        // Disabled without a context
+        let mut disabled =
+            Checkpoint::<usize>::with_context("seeds", None).interval(Duration::ZERO);
+        assert!(!disabled.tick(|| 1).unwrap());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,209 @@
+//! Client for the Advent of Code website, used by the `aoc` command.
+//!
+//! Requests are authenticated with the `session` cookie from a logged in browser,
+//! read from `AOC_SESSION`, or from the file in `AOC_SESSION_FILE` (default `.session`
+//! in the repository root).
+
+use std::path::{Path, PathBuf};
+
+use crate::{opt, Error, Result};
+
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+pub const DEFAULT_YEAR: u16 = 2023;
+
+/// Identifies this tool to the site, as its operators ask automated tools to do.
+/// Set `AOC_USER_AGENT` to add contact details.
+const USER_AGENT: &str = concat!("aoc2023-tools/", env!("CARGO_PKG_VERSION"));
+
+pub struct Client {
+    base_url: String,
+    year: u16,
+    session: String,
+    user_agent: String,
+    agent: ureq::Agent,
+}
+
+impl Client {
+    pub fn new(base_url: &str, year: u16, session: &str) -> Self {
+        let user_agent = match std::env::var("AOC_USER_AGENT") {
+            Ok(contact) if !contact.trim().is_empty() => {
+                format!("{USER_AGENT} ({})", contact.trim())
+            }
+            _ => USER_AGENT.to_owned(),
+        };
+
+        Self {
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            year,
+            session: session.to_owned(),
+            user_agent,
+            agent: ureq::AgentBuilder::new().redirects(0).build(),
+        }
+    }
+
+    /// Creates a client with the session token from the environment.
+    pub fn from_env(base_url: &str, year: u16) -> Result<Self> {
+        Ok(Self::new(base_url, year, &session_token()?))
+    }
+
+    /// Downloads a day's puzzle input.
+    pub fn fetch_input(&self, day: u8) -> Result<String> {
+        let url = format!("{}/{}/day/{day}/input", self.base_url, self.year);
+        self.send(&url, self.agent.get(&url), None)
+    }
+
+    /// Submits an answer for a day's part, returning the response page.
+    pub fn post_answer(&self, day: u8, part: u8, answer: &str) -> Result<String> {
+        let url = format!("{}/{}/day/{day}/answer", self.base_url, self.year);
+        let level = part.to_string();
+        let form = [("level", level.as_str()), ("answer", answer)];
+        self.send(&url, self.agent.post(&url), Some(&form))
+    }
+
+    /// Sends a request with the session cookie and User-Agent, and an optional form body.
+    /// Returns the response body.
+    fn send(
+        &self,
+        url: &str,
+        request: ureq::Request,
+        form: Option<&[(&str, &str)]>,
+    ) -> Result<String> {
+        let request = request
+            .set("Cookie", &format!("session={}", self.session))
+            .set("User-Agent", &self.user_agent);
+
+        let response = match form {
+            Some(form) => request.send_form(form),
+            None => request.call(),
+        };
+        match response {
+            Ok(response) => response.into_string().map_err(|e| Error::http(url, e)),
+            Err(ureq::Error::Status(code, response)) => {
+                let body = response.into_string().unwrap_or_default();
+                let reason = body.lines().next().unwrap_or("").trim();
+                Err(Error::http(url, format!("status {code} {reason}")))
+            }
+            Err(e) => Err(Error::http(url, e)),
+        }
+    }
+}
+
+/// Gets the session token from `AOC_SESSION`, or the session file.
+fn session_token() -> Result<String> {
+    if let Ok(token) = std::env::var("AOC_SESSION") {
+        if !token.trim().is_empty() {
+            return Ok(token.trim().to_owned());
+        }
+    }
+
+    let path = std::env::var_os("AOC_SESSION_FILE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| opt::root_dir().join(".session"));
+    match std::fs::read_to_string(&path) {
+        Ok(token) if !token.trim().is_empty() => Ok(token.trim().to_owned()),
+        Ok(_) => Err(Error::Usage(format!(
+            "session file {} is empty",
+            path.display()
+        ))),
+        Err(e) => Err(Error::Usage(format!(
+            "no session token, set AOC_SESSION or save it in {} ({e})",
+            path.display()
+        ))),
+    }
+}
+
+/// Saves the input from `fetch` to `input.txt` in `dir`, unless it is already there.
+/// Returns the input file, and whether it was downloaded.
+pub fn fetch_cached<F>(dir: &Path, fetch: F) -> Result<(PathBuf, bool)>
+where
+    F: FnOnce() -> Result<String>,
+{
+    let path = dir.join("input.txt");
+    if path.exists() {
+        return Ok((path, false));
+    }
+
+    let input = fetch()?;
+    std::fs::create_dir_all(dir).map_err(|e| Error::io(dir, e))?;
+    std::fs::write(&path, input).map_err(|e| Error::io(&path, e))?;
+
+    Ok((path, true))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+
+    /// Serves one canned response per request on a local port, sending back each request's text.
+    pub fn stub_server(responses: Vec<(u16, &'static str)>) -> (String, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::channel();
+
+        std::thread::spawn(move || {
+            for (status, body) in responses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+
+                let mut request = String::new();
+                let mut length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if let Some((_, value)) = line.to_lowercase().split_once("content-length:") {
+                        length = value.trim().parse().unwrap();
+                    }
+                    request.push_str(&line);
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                }
+                let mut content = vec![0; length];
+                std::io::Read::read_exact(&mut reader, &mut content).unwrap();
+                request.push_str(&String::from_utf8_lossy(&content));
+                tx.send(request).unwrap();
+
+                let mut stream = reader.into_inner();
+                write!(
+                    stream,
+                    "HTTP/1.1 {status} Stub\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                )
+                .unwrap();
+            }
+        });
+
+        (url, rx)
+    }
+
+    #[test]
+    pub fn fetch_and_cache() {
+        let (url, requests) = stub_server(vec![(200, "1 2 3\n"), (404, "Not found\n")]);
+        let client = Client::new(&url, 2023, "abc123");
+        let dir = std::env::temp_dir().join(format!("tools-fetch-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let (path, downloaded) = fetch_cached(&dir, || client.fetch_input(4)).unwrap();
+        assert!(downloaded);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "1 2 3\n");
+
+        let request = requests.recv().unwrap();
+        assert!(request.starts_with("GET /2023/day/4/input "), "{request}");
+        assert!(request.contains("Cookie: session=abc123"), "{request}");
+        assert!(
+            request.contains(&format!("User-Agent: {USER_AGENT}")),
+            "{request}"
+        );
+
+        // Cached input is never downloaded again
+        assert!(!fetch_cached(&dir, || client.fetch_input(4)).unwrap().1);
+
+        let err = client.fetch_input(5).unwrap_err().to_string();
+        assert!(err.ends_with("status 404 Not found"), "{err}");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,151 @@
+//! Parse errors that point into the input, shown with the offending line underlined:
+//! ```text
+//! day7/input.txt:3:2: parse error: invalid card 'X'
+//!   |
+//! 3 | 3XT3K 765
+//!   |  ^
+//! ```
+
+use std::fmt::Display;
+use std::path::PathBuf;
+
+/// A problem at a span of the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub path: Option<PathBuf>,
+    /// 1-based line number.
+    pub line: usize,
+    /// 1-based column of the span start, in characters, if known.
+    pub column: Option<usize>,
+    /// Width of the span, in characters.
+    pub len: usize,
+    pub message: String,
+    /// The input line, shown under the message once known.
+    pub source: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn new(line: usize, column: Option<usize>, message: impl Into<String>) -> Self {
+        Self {
+            path: None,
+            line,
+            column,
+            len: 1,
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    /// Diagnostic for `span`, a slice of `text`, which is located and underlined.
+    /// The line number is relative to `text`.
+    pub fn at_span(text: &str, span: &str, message: impl Into<String>) -> Self {
+        let offset = (span.as_ptr() as usize)
+            .checked_sub(text.as_ptr() as usize)
+            .filter(|offset| *offset <= text.len())
+            .expect("span is a slice of text");
+        let (line, column) = locate(text, offset);
+
+        let line_start = text[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let line_end = text[offset..]
+            .find('\n')
+            .map(|i| offset + i)
+            .unwrap_or(text.len());
+
+        Self {
+            column: Some(column),
+            len: span.chars().count().max(1),
+            source: Some(text[line_start..line_end].trim_end().to_owned()),
+            ..Self::new(line, None, message)
+        }
+    }
+
+    /// Sets the input line from the whole input, if not already known.
+    pub fn with_source(mut self, input: &str) -> Self {
+        if self.source.is_none() {
+            self.source = crate::input::lines(input)
+                .nth(self.line.wrapping_sub(1))
+                .map(str::to_owned);
+        }
+        self
+    }
+
+    /// Formats `path:line:column`, with whatever location is known.
+    fn location(&self) -> String {
+        let path = self
+            .path
+            .as_ref()
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|| "<input>".to_owned());
+        match self.column {
+            Some(column) => format!("{path}:{}:{column}", self.line),
+            None => format!("{path}:{}", self.line),
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: parse error: {}", self.location(), self.message)?;
+
+        let Some(source) = &self.source else {
+            return Ok(());
+        };
+        let number = self.line.to_string();
+        let gutter = " ".repeat(number.len());
+        // Tabs would misalign the caret, so they are shown as single spaces
+        let source = source.replace('\t', " ");
+
+        write!(f, "\n{gutter} |\n{number} | {source}")?;
+        if let Some(column) = self.column {
+            let indent = " ".repeat(column - 1);
+            let carets = "^".repeat(self.len);
+            write!(f, "\n{gutter} | {indent}{carets}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Gets the 1-based line and column of a byte offset in `text`.
+fn locate(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let column = before[line_start..].chars().count() + 1;
+
+    (line, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn renders_caret() {
+        let text = "32T3K 765\nT5X5J 684\n";
+        let diagnostic = Diagnostic::at_span(text, &text[12..13], "invalid card 'X'");
+        assert_eq!((diagnostic.line, diagnostic.column), (2, Some(3)));
+        assert_eq!(
+            diagnostic.to_string(),
+            "<input>:2:3: parse error: invalid card 'X'\n  |\n2 | T5X5J 684\n  |   ^"
+        );
+
+        let bid = Diagnostic::at_span(text, &text[16..19], "invalid bid");
+        assert!(bid.to_string().ends_with("\n  |       ^^^"), "{bid}");
+    }
+
+    #[test]
+    pub fn source_from_input() {
+        let diagnostic = Diagnostic::new(10, None, "expected `<hand> <bid>`");
+        let input = "1\n2\n3\n4\n5\n6\n7\n8\n9\nJJJJ\n";
+        assert_eq!(
+            diagnostic.with_source(input).to_string(),
+            "<input>:10: parse error: expected `<hand> <bid>`\n   |\n10 | JJJJ"
+        );
+
+        // Lines past the end are shown without a source line
+        assert_eq!(
+            Diagnostic::new(11, None, "x").with_source(input).to_string(),
+            "<input>:11: parse error: x"
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use std::fmt::{Debug, Display};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::diagnostic::Diagnostic;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+pub enum Error {
+    /// Input could not be read.
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// A value in the input could not be parsed.
+    Parse(Diagnostic),
+    /// The input was readable, but does not fit the puzzle.
+    Validation {
+        path: Option<PathBuf>,
+        line: Option<usize>,
+        message: String,
+    },
+    /// A request to the puzzle website failed.
+    Http { url: String, message: String },
+    /// The command line options cannot be used together.
+    Usage(String),
+    /// The selected part has no solution yet.
+    Unsolved,
+    /// Some answers did not match the recorded solution.
+    CheckFailed(usize),
+    /// The solver stopped because its part was cancelled.
+    Cancelled,
+    /// A part ran past `--timeout` and was cancelled.
+    TimedOut { part: u8, limit: Duration },
+}
+
+impl Error {
+    pub fn io(path: &Path, source: std::io::Error) -> Self {
+        Error::Io {
+            path: path.to_owned(),
+            source,
+        }
+    }
+
+    pub fn parse(line: usize, message: impl Into<String>) -> Self {
+        Error::Parse(Diagnostic::new(line, None, message))
+    }
+
+    pub fn http(url: &str, message: impl Display) -> Self {
+        Error::Http {
+            url: url.to_owned(),
+            message: message.to_string(),
+        }
+    }
+
+    /// Parse error at a 1-based line and column.
+    pub fn parse_at(line: usize, column: usize, message: impl Into<String>) -> Self {
+        Error::Parse(Diagnostic::new(line, Some(column), message))
+    }
+
+    /// Parse error underlining `span`, a slice of `text`. The line is relative to `text`,
+    /// so for a single line of the input, set the real line with [`Error::at_line`].
+    pub fn parse_in(text: &str, span: &str, message: impl Into<String>) -> Self {
+        Error::Parse(Diagnostic::at_span(text, span, message))
+    }
+
+    pub fn validation(message: impl Into<String>) -> Self {
+        Error::Validation {
+            path: None,
+            line: None,
+            message: message.into(),
+        }
+    }
+
+    /// Sets the line number this error refers to (1-based).
+    pub fn at_line(mut self, n: usize) -> Self {
+        match &mut self {
+            Error::Parse(diagnostic) => diagnostic.line = n,
+            Error::Validation { line, .. } => *line = Some(n),
+            _ => {}
+        }
+        self
+    }
+
+    /// Shifts the line of a parse error down, for errors from parsing a block of the input.
+    pub fn offset_line(mut self, n: usize) -> Self {
+        if let Error::Parse(diagnostic) = &mut self {
+            diagnostic.line += n;
+        }
+        self
+    }
+
+    /// Shifts the column of a parse error right, for errors from parsing part of a line.
+    pub fn offset_column(mut self, n: usize) -> Self {
+        if let Error::Parse(Diagnostic {
+            column: Some(column),
+            ..
+        }) = &mut self
+        {
+            *column += n;
+        }
+        self
+    }
+
+    /// Sets the input file this error refers to, if not already set.
+    pub fn with_path(mut self, file: &Path) -> Self {
+        match &mut self {
+            Error::Parse(Diagnostic { path, .. }) | Error::Validation { path, .. } => {
+                path.get_or_insert_with(|| file.to_owned());
+            }
+            _ => {}
+        }
+        self
+    }
+
+    /// Attaches the input line a parse error refers to, so it is shown with the error.
+    pub fn with_source(self, input: &str) -> Self {
+        match self {
+            Error::Parse(diagnostic) => Error::Parse(diagnostic.with_source(input)),
+            other => other,
+        }
+    }
+}
+
+/// Formats `path:line` for whatever location info is known.
+fn location(path: &Option<PathBuf>, line: Option<usize>) -> String {
+    let path = path
+        .as_ref()
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|| "<input>".to_owned());
+    match line {
+        Some(line) => format!("{path}:{line}"),
+        None => path,
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io { path, source } => {
+                write!(f, "failed to read {}: {}", path.display(), source)
+            }
+            Error::Parse(diagnostic) => write!(f, "{diagnostic}"),
+            Error::Validation {
+                path,
+                line,
+                message,
+            } => write!(f, "{}: invalid input: {}", location(path, *line), message),
+            Error::Http { url, message } => write!(f, "request to {url} failed: {message}"),
+            Error::Usage(message) => write!(f, "{message}"),
+            Error::Unsolved => write!(f, "this part is not solved yet"),
+            Error::CheckFailed(n) => write!(f, "{n} answer(s) did not match the recorded solution"),
+            Error::Cancelled => write!(f, "cancelled"),
+            Error::TimedOut { part, limit } => write!(f, "part {part} timed out after {limit:?}"),
+        }
+    }
+}
+
+/// Same as Display, so returning an error from `main` prints something readable.
+impl Debug for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,180 @@
+//! Parsing lines by their format, with `#[derive(AocParse)]`:
+//! ```
+//! use tools::AocParse;
+//!
+//! #[derive(AocParse)]
+//! #[aoc(format = "{src}-to-{dst} map:")]
+//! struct Title {
+//!     src: String,
+//!     dst: String,
+//! }
+//!
+//! let title: Title = "seed-to-soil map:".parse().unwrap();
+//! assert_eq!((title.src.as_str(), title.dst.as_str()), ("seed", "soil"));
+//! ```
+//! The derived `FromStr` reads the line with a [`Scanner`], which reports where it failed.
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::{Error, Result};
+
+/// Reads a line from the start, one literal or field at a time.
+pub struct Scanner<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    pub fn new(text: &'a str) -> Self {
+        Self { text, pos: 0 }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    /// Error pointing at the next character, or the end of the line.
+    fn error_here(&self, message: String) -> Error {
+        let rest = self.rest();
+        let next = rest.chars().next().map_or(0, char::len_utf8);
+        Error::parse_in(self.text, &rest[..next], message)
+    }
+
+    /// Skips `literal`, which must come next.
+    pub fn literal(&mut self, literal: &str) -> Result<()> {
+        if !self.rest().starts_with(literal) {
+            return Err(self.error_here(format!("expected `{literal}`")));
+        }
+        self.pos += literal.len();
+        Ok(())
+    }
+
+    /// Parses the field `name` from the text up to `until`, or to the end of the line.
+    pub fn field<T>(&mut self, name: &str, until: Option<&str>) -> Result<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let rest = self.rest();
+        let end = match until {
+            Some(until) => rest.find(until).ok_or_else(|| {
+                Error::parse_in(self.text, rest, format!("expected `{until}` after {name}"))
+            })?,
+            None => rest.len(),
+        };
+        let value = &rest[..end];
+        self.pos += end;
+
+        value
+            .parse()
+            .map_err(|e| Error::parse_in(self.text, value, format!("invalid {name}: {e}")))
+    }
+
+    /// Checks that the whole line was read.
+    pub fn finish(&self) -> Result<()> {
+        let rest = self.rest();
+        if !rest.is_empty() {
+            return Err(Error::parse_in(
+                self.text,
+                rest,
+                "unexpected text at end of line",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Picks the error that got furthest into the line, from trying several formats.
+pub fn furthest(errors: Vec<Error>) -> Error {
+    let column = |e: &Error| match e {
+        Error::Parse(diagnostic) => diagnostic.column.unwrap_or(0),
+        _ => 0,
+    };
+
+    let mut errors = errors.into_iter();
+    let first = errors.next().expect("at least one format was tried");
+    errors.fold(
+        first,
+        |best, e| if column(&e) > column(&best) { e } else { best },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{AocParse, Diagnostic, Error};
+
+    #[derive(Debug, PartialEq, AocParse)]
+    #[aoc(format = "{address} = ({left}, {right})")]
+    struct Node {
+        address: String,
+        left: String,
+        right: String,
+    }
+
+    #[derive(Debug, PartialEq, AocParse)]
+    enum Step {
+        #[aoc(format = "{label}={focal}")]
+        Add { label: String, focal: u8 },
+        #[aoc(format = "{label}-")]
+        Remove { label: String },
+    }
+
+    fn column(e: Error) -> (Option<usize>, String) {
+        match e {
+            Error::Parse(Diagnostic {
+                column, message, ..
+            }) => (column, message),
+            e => panic!("not a parse error: {e}"),
+        }
+    }
+
+    #[test]
+    pub fn derived() {
+        let node: Node = "AAA = (BBB, CCC)".parse().unwrap();
+        assert_eq!(
+            node,
+            Node {
+                address: "AAA".to_owned(),
+                left: "BBB".to_owned(),
+                right: "CCC".to_owned()
+            }
+        );
+
+        let e = "AAA = [BBB, CCC)".parse::<Node>().unwrap_err();
+        assert_eq!(
+            column(e),
+            (Some(1), "expected ` = (` after address".to_owned())
+        );
+        let e = "AAA = (BBB CCC)".parse::<Node>().unwrap_err();
+        assert_eq!(column(e), (Some(8), "expected `, ` after left".to_owned()));
+        let e = "AAA = (BBB, CCC)x".parse::<Node>().unwrap_err();
+        assert_eq!(
+            column(e),
+            (Some(17), "unexpected text at end of line".to_owned())
+        );
+    }
+
+    #[test]
+    pub fn derived_enum() {
+        let add = Step::Add {
+            label: "rn".to_owned(),
+            focal: 1,
+        };
+        assert_eq!("rn=1".parse::<Step>().unwrap(), add);
+        let remove = Step::Remove {
+            label: "cm".to_owned(),
+        };
+        assert_eq!("cm-".parse::<Step>().unwrap(), remove);
+
+        // The variant that read the most reports the error
+        let e = "rn=x".parse::<Step>().unwrap_err();
+        assert_eq!(
+            column(e),
+            (
+                Some(4),
+                "invalid focal: invalid digit found in string".to_owned()
+            )
+        );
+    }
+}
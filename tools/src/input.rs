@@ -0,0 +1,233 @@
+//! Helpers for splitting and parsing puzzle input, independent of line endings.
+
+use std::{fmt::Display, str::FromStr};
+
+use crate::{Error, Result};
+
+/// Iterates over lines, with trailing whitespace (including any `\r`) removed.
+pub fn lines(text: &str) -> impl Iterator<Item = &str> {
+    text.lines().map(str::trim_end)
+}
+
+/// Iterates over blocks of lines separated by one or more blank lines.
+///
+/// Each block is a slice of the original text, from the start of its first line
+/// to the end of its last line, so it can be split further with [`lines`].
+pub fn blocks(text: &str) -> Blocks<'_> {
+    Blocks { text, pos: 0 }
+}
+
+pub struct Blocks<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Iterator for Blocks<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut start = None;
+        let mut end = self.pos;
+
+        for line in self.text[self.pos..].split_inclusive('\n') {
+            let line_start = self.pos;
+            self.pos += line.len();
+
+            let content = line.trim_end();
+            if content.is_empty() {
+                // Blank line ends the current block, or is skipped if none started yet
+                if start.is_some() {
+                    break;
+                }
+            } else {
+                start.get_or_insert(line_start);
+                end = line_start + content.len();
+            }
+        }
+
+        start.map(|start| &self.text[start..end])
+    }
+}
+
+/// Gets the 1-based line in `text` where `part`, a slice of it, starts.
+/// Use it with [`Error::offset_line`] for errors from parsing a block.
+pub fn line_of(text: &str, part: &str) -> usize {
+    let offset = part.as_ptr() as usize - text.as_ptr() as usize;
+    text[..offset].matches('\n').count() + 1
+}
+
+/// Parses all whitespace-separated values in `text`, e.g. `"1 -2  3"`.
+pub fn ints<T>(text: &str) -> Result<Vec<T>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    parse_words(text, 0)
+}
+
+/// Parses a `Label: numbers` line into the label and its values, e.g. `"Time:  7 15"`.
+pub fn labeled_ints<T>(line: &str) -> Result<(&str, Vec<T>)>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let Some((label, _)) = line.split_once(':') else {
+        return Err(Error::parse_at(1, 1, "expected `label: values`"));
+    };
+    let values = parse_words(line, label.len() + 1)?;
+
+    Ok((label.trim(), values))
+}
+
+/// A rectangular grid of bytes, borrowing its rows from the input.
+#[derive(Debug, Clone)]
+pub struct ByteGrid<'a> {
+    rows: Vec<&'a [u8]>,
+}
+
+impl<'a> ByteGrid<'a> {
+    /// Views `text` as a grid, failing if it is empty or its rows differ in width.
+    pub fn new(text: &'a str) -> Result<Self> {
+        let rows: Vec<&[u8]> = lines(text.trim_end()).map(str::as_bytes).collect();
+        let width = rows.first().map_or(0, |row| row.len());
+        if width == 0 {
+            return Err(Error::validation("grid is empty"));
+        }
+        if let Some(y) = rows.iter().position(|row| row.len() != width) {
+            return Err(Error::validation(format!(
+                "row is {} wide, expected {width}",
+                rows[y].len()
+            ))
+            .at_line(y + 1));
+        }
+
+        Ok(Self { rows })
+    }
+
+    pub fn width(&self) -> usize {
+        self.rows[0].len()
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Gets the byte at column `x` of row `y`, or `None` outside the grid.
+    pub fn get(&self, x: usize, y: usize) -> Option<u8> {
+        self.rows.get(y)?.get(x).copied()
+    }
+
+    pub fn row(&self, y: usize) -> &'a [u8] {
+        self.rows[y]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &'a [u8]> + '_ {
+        self.rows.iter().copied()
+    }
+
+    /// Checks every byte with `valid`, failing at the first it rejects.
+    pub fn check<F>(&self, valid: F) -> Result<()>
+    where
+        F: Fn(u8) -> bool,
+    {
+        for (y, row) in self.rows().enumerate() {
+            if let Some(x) = row.iter().position(|b| !valid(*b)) {
+                let c = row[x] as char;
+                return Err(Error::parse_at(y + 1, x + 1, format!("unexpected character {c:?}")));
+            }
+        }
+        Ok(())
+    }
+
+    /// Finds the first `byte`, scanning rows top to bottom, as `(x, y)`.
+    pub fn find(&self, byte: u8) -> Option<(usize, usize)> {
+        self.rows()
+            .enumerate()
+            .find_map(|(y, row)| Some((row.iter().position(|b| *b == byte)?, y)))
+    }
+}
+
+/// Parses whitespace-separated words in `text[start..]`, reporting errors relative to `text`.
+fn parse_words<T>(text: &str, start: usize) -> Result<Vec<T>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    text[start..]
+        .split_whitespace()
+        .map(|word| {
+            word.parse::<T>()
+                .map_err(|e| Error::parse_in(text, word, format!("{word:?}: {e}")))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostic::Diagnostic;
+
+    #[test]
+    pub fn lines_ignore_endings() {
+        let lf: Vec<&str> = lines("ab\ncd \n\nef").collect();
+        let crlf: Vec<&str> = lines("ab\r\ncd \r\n\r\nef\r\n").collect();
+
+        assert_eq!(lf, vec!["ab", "cd", "", "ef"]);
+        assert_eq!(lf, crlf);
+    }
+
+    #[test]
+    pub fn blocks_ignore_endings() {
+        let lf: Vec<&str> = blocks("a\nb\n\nc\n").collect();
+        let crlf: Vec<&str> = blocks("a\r\nb\r\n\r\nc\r\n").collect();
+
+        assert_eq!(lf, vec!["a\nb", "c"]);
+        assert_eq!(crlf, vec!["a\r\nb", "c"]);
+    }
+
+    #[test]
+    pub fn blocks_skip_extra_blank_lines() {
+        let text = "\n \na\n  \n\t\n\nb  \nc\n\n";
+        let blocks: Vec<&str> = blocks(text).collect();
+
+        assert_eq!(blocks, vec!["a", "b  \nc"]);
+        assert_eq!(line_of(text, blocks[1]), 7);
+        assert_eq!(lines(blocks[1]).collect::<Vec<_>>(), vec!["b", "c"]);
+    }
+
+    #[test]
+    pub fn ints_report_position() {
+        assert_eq!(ints::<i32>(" 1 -2\n 3").unwrap(), vec![1, -2, 3]);
+
+        let err = ints::<u32>("1 2\n3 x4").unwrap_err();
+        assert!(matches!(err, Error::Parse(Diagnostic { line: 2, column: Some(3), len: 2, .. })), "{err}");
+    }
+
+    #[test]
+    pub fn labeled() {
+        let (label, values) = labeled_ints::<u64>("Time:      7  15   30").unwrap();
+        assert_eq!(label, "Time");
+        assert_eq!(values, vec![7, 15, 30]);
+
+        let err = labeled_ints::<u64>("Distance: 9 4o").unwrap_err();
+        assert!(matches!(err, Error::Parse(Diagnostic { line: 1, column: Some(13), .. })), "{err}");
+        assert!(labeled_ints::<u64>("9 40").is_err());
+    }
+
+    #[test]
+    pub fn byte_grids() {
+        let grid = ByteGrid::new("#.S\r\n..#\r\n").unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid.get(2, 1), Some(b'#'));
+        assert_eq!(grid.get(3, 0), None);
+        assert_eq!(grid.row(1), b"..#");
+        assert_eq!(grid.find(b'S'), Some((2, 0)));
+        assert!(grid.check(|b| b"#.S".contains(&b)).is_ok());
+        let err = grid.check(|b| b != b'S').unwrap_err();
+        assert!(err.to_string().starts_with("<input>:1:3"), "{err}");
+
+        let err = ByteGrid::new("##\n#\n").unwrap_err();
+        assert!(err.to_string().starts_with("<input>:2"), "{err}");
+        assert!(ByteGrid::new("\n").is_err());
+    }
+}
@@ -1,5 +1,34 @@
-mod opt;
+// Lets the code derived by `tools_derive` refer to `::tools` from inside this crate too
+extern crate self as tools;
+
 mod arena;
+mod check;
+mod error;
+mod opt;
+mod solution;
+
+pub mod bench;
+pub mod buffer;
+pub mod cache;
+pub mod cancel;
+pub mod checkpoint;
+pub mod client;
+pub mod diagnostic;
+pub mod format;
+pub mod input;
+pub mod log;
+pub mod memory;
+pub mod params;
+pub mod profile;
+pub mod progress;
+pub mod shard;
+pub mod submit;
 
-pub use opt::Opt;
-pub use arena::{Arena, Position, Direction};
+pub use arena::{Arena, Direction, Position};
+pub use check::{Answers, Verdict};
+pub use diagnostic::Diagnostic;
+pub use error::{Error, Result};
+pub use opt::{day_dir, root_dir, Format, Opt, Part};
+pub use params::{param, param_or};
+pub use solution::{run, Solution};
+pub use tools_derive::AocParse;
@@ -0,0 +1,96 @@
+//! Diagnostic output for solvers, written to stderr and filtered by `-v/-vv/-q`.
+//!
+//! Solvers log through the [`error!`](crate::error), [`warn!`](crate::warn),
+//! [`info!`](crate::info), [`debug!`](crate::debug) and [`trace!`](crate::trace) macros,
+//! which take the same arguments as `println!`.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// How much diagnostic output to show, from least to most.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error = 1,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+static LEVEL: AtomicU8 = AtomicU8::new(Level::Warn as u8);
+
+impl Level {
+    /// Gets the level for the `-q` flag and number of `-v` flags.
+    /// By default only warnings and errors are shown.
+    pub fn from_flags(quiet: bool, verbose: u8) -> Self {
+        match (quiet, verbose) {
+            (true, _) => Level::Error,
+            (false, 0) => Level::Warn,
+            (false, 1) => Level::Debug,
+            (false, _) => Level::Trace,
+        }
+    }
+}
+
+/// Sets the most detailed level that is shown.
+pub fn set_level(level: Level) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+/// Whether messages at `level` are shown.
+pub fn enabled(level: Level) -> bool {
+    level as u8 <= LEVEL.load(Ordering::Relaxed)
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! log {
+    ($level:expr, $($arg:tt)*) => {
+        if $crate::log::enabled($level) {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)*) => { $crate::log!($crate::log::Level::Error, $($arg)*) };
+}
+
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)*) => { $crate::log!($crate::log::Level::Warn, $($arg)*) };
+}
+
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)*) => { $crate::log!($crate::log::Level::Info, $($arg)*) };
+}
+
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)*) => { $crate::log!($crate::log::Level::Debug, $($arg)*) };
+}
+
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)*) => { $crate::log!($crate::log::Level::Trace, $($arg)*) };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn levels() {
+        assert_eq!(Level::from_flags(false, 0), Level::Warn);
+        assert_eq!(Level::from_flags(false, 1), Level::Debug);
+        assert_eq!(Level::from_flags(false, 5), Level::Trace);
+        assert_eq!(Level::from_flags(true, 0), Level::Error);
+
+        set_level(Level::Debug);
+        assert!(enabled(Level::Warn));
+        assert!(enabled(Level::Debug));
+        assert!(!enabled(Level::Trace));
+        set_level(Level::Warn);
+    }
+}
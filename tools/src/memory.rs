@@ -0,0 +1,154 @@
+//! Heap usage tracking through a counting global allocator.
+//!
+//! The allocator is only installed with the `memory` feature, e.g.
+//! `cargo run --release --features tools/memory`. Without it all counters stay at zero.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fmt::Display;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use humansize::{format_size, DECIMAL};
+
+/// Wraps the system allocator, counting every allocation.
+pub struct CountingAlloc;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+static TOTAL: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+#[cfg(feature = "memory")]
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+impl CountingAlloc {
+    fn added(size: usize) {
+        let current = CURRENT.fetch_add(size, Ordering::Relaxed) + size;
+        PEAK.fetch_max(current, Ordering::Relaxed);
+        TOTAL.fetch_add(size, Ordering::Relaxed);
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn removed(size: usize) {
+        CURRENT.fetch_sub(size, Ordering::Relaxed);
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            Self::added(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            Self::added(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        Self::removed(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            Self::removed(layout.size());
+            Self::added(new_size);
+        }
+        new_ptr
+    }
+}
+
+/// Whether the counting allocator is installed.
+pub fn enabled() -> bool {
+    cfg!(feature = "memory")
+}
+
+/// Heap usage counters, in bytes except for `allocations`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Usage {
+    pub current: usize,
+    pub peak: usize,
+    pub total: usize,
+    pub allocations: usize,
+}
+
+impl Usage {
+    /// Gets the counters now.
+    pub fn now() -> Self {
+        Usage {
+            current: CURRENT.load(Ordering::Relaxed),
+            peak: PEAK.load(Ordering::Relaxed),
+            total: TOTAL.load(Ordering::Relaxed),
+            allocations: ALLOCATIONS.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Starts measuring a new peak from the current usage, and returns the counters.
+    pub fn start() -> Self {
+        PEAK.store(CURRENT.load(Ordering::Relaxed), Ordering::Relaxed);
+        Self::now()
+    }
+
+    /// Gets what changed since `start`, with `peak` being the most held above the start.
+    pub fn since(start: &Usage) -> Self {
+        let now = Self::now();
+        Usage {
+            current: now.current.saturating_sub(start.current),
+            peak: now.peak.saturating_sub(start.current),
+            total: now.total - start.total,
+            allocations: now.allocations - start.allocations,
+        }
+    }
+}
+
+impl Display for Usage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "peak {}, still held {}, {} allocated in {} allocations",
+            format_size(self.peak, DECIMAL),
+            format_size(self.current, DECIMAL),
+            format_size(self.total, DECIMAL),
+            self.allocations
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn counts() {
+        let start = Usage::start();
+        CountingAlloc::added(1000);
+        CountingAlloc::removed(1000);
+        let usage = Usage::since(&start);
+
+        assert!(usage.peak >= 1000);
+        assert!(usage.total >= 1000);
+        assert!(usage.allocations >= 1);
+    }
+
+    #[test]
+    pub fn report() {
+        let usage = Usage {
+            current: 400,
+            peak: 1100,
+            total: 3000,
+            allocations: 30,
+        };
+        assert_eq!(
+            usage.to_string(),
+            "peak 1.10 kB, still held 400 B, 3 kB allocated in 30 allocations"
+        );
+    }
+}
@@ -1,36 +1,345 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::OnceLock;
+use std::time::Duration;
 use structopt::StructOpt;
 
+use crate::buffer::Buffer;
+use crate::client;
+use crate::log::{self, Level};
+use crate::params::{self, Params};
+use crate::shard::Shard;
+use crate::{Error, Result};
+
 #[derive(Debug, StructOpt)]
 #[structopt(name = "aoc2023_day5", about = "Advent of Code 2023 - Day 5 Solution")]
 pub struct Opt {
-    /// Enable part1 input parsing
-    #[structopt(long = "part1")]
-    pub is_part1: bool,
+    /// Part to solve: 1, 2 or both
+    #[structopt(long = "part", default_value = "both")]
+    pub part: Part,
 
-    /// Input file path
+    /// Use an example input: `example.txt`, or a name or index like `example2` or `2`
     #[structopt(long = "example")]
-    pub is_example: bool,
+    pub example: Option<Option<String>>,
+
+    /// Read input from this path instead, or from stdin if `-`
+    #[structopt(long = "input", parse(from_os_str), conflicts_with = "example")]
+    pub input: Option<PathBuf>,
+
+    /// Set a puzzle parameter, overriding the day's `aoc.toml`, e.g. `--param cycles=1000`
+    #[structopt(long = "param", parse(try_from_str = params::parse_arg), number_of_values = 1)]
+    pub params: Vec<(String, String)>,
+
+    /// Day to solve, used to find its directory. Defaults to the day in the binary name
+    #[structopt(long = "day")]
+    pub day: Option<u8>,
+
+    /// Compare answers with the input's recorded solution file
+    #[structopt(long = "check")]
+    pub check: bool,
+
+    /// Time parsing and the selected part(s) over this many runs, after a warm-up run
+    #[structopt(long = "bench")]
+    pub bench: Option<usize>,
+
+    /// Cancel a part that runs longer than this, e.g. `30s`, `2m` or `500ms`
+    #[structopt(long = "timeout", parse(try_from_str = parse_duration), conflicts_with = "bench")]
+    pub timeout: Option<Duration>,
+
+    /// Only search this shard of the work, e.g. `2/4`, for `aoc merge` to combine later
+    #[structopt(long = "shard", conflicts_with_all = &["check", "submit"])]
+    pub shard: Option<Shard>,
+
+    /// Continue long running parts from their last checkpoint, if saved for this input
+    #[structopt(long = "resume", conflicts_with = "bench")]
+    pub resume: bool,
+
+    /// Solve again even if the answers for this input and build are cached
+    #[structopt(long = "no-cache")]
+    pub no_cache: bool,
+
+    /// Submit the answers to the puzzle website, see `aoc submit`
+    #[structopt(long = "submit", conflicts_with_all = &["example", "input", "bench"])]
+    pub submit: bool,
+
+    /// Puzzle website to submit answers to
+    #[structopt(long = "base-url", env = "AOC_BASE_URL", default_value = client::DEFAULT_BASE_URL)]
+    pub base_url: String,
+
+    /// Write timings of parsing, each part and solver spans to this Chrome trace file
+    #[structopt(long = "trace", parse(from_os_str))]
+    pub trace: Option<PathBuf>,
+
+    /// Output format for answers: text or json
+    #[structopt(long = "format", default_value = "text")]
+    pub format: Format,
+
+    /// Show debug output from the solver, repeat for more detail
+    #[structopt(short = "v", parse(from_occurrences))]
+    pub verbose: u8,
+
+    /// Only show errors from the solver
+    #[structopt(short = "q", long = "quiet", conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Input loaded by the first call to `buffer`
+    #[structopt(skip)]
+    buffer: OnceLock<Buffer>,
 }
 
 impl Opt {
+    /// Parses the command line, and sets the log level from it.
     pub fn load() -> Self {
-        Self::from_args()
+        let opt = Self::from_args();
+        log::set_level(Level::from_flags(opt.quiet, opt.verbose));
+        opt
     }
 
-    pub fn file(&self) -> PathBuf {
-        if self.is_example {
-            PathBuf::from("example.txt")
-        } else {
-            PathBuf::from("input.txt")
+    /// Gets the selected input path, `-` means stdin.
+    /// Example and puzzle inputs are looked up in the day's directory.
+    pub fn file(&self) -> Result<PathBuf> {
+        if let Some(path) = &self.input {
+            return Ok(path.clone());
+        }
+
+        let dir = self.day_dir();
+        match &self.example {
+            Some(name) => find_example(&dir, name.as_deref()),
+            None => find_input(&dir),
         }
     }
 
-    pub fn input(&self) -> String {
-        std::fs::read_to_string(self.file()).unwrap()
+    /// Gets the day number from `--day`, or else from the binary or package name, e.g. 4 for `day4`.
+    pub fn day(&self) -> Option<u8> {
+        let day_of = |name: &str| name.strip_prefix("day")?.parse().ok();
+
+        let exe = std::env::args_os().next().map(PathBuf::from);
+        self.day
+            .or_else(|| day_of(exe.as_ref()?.file_stem()?.to_str()?))
+            // Binaries not named after their day (like day5's `main`) still get it from `cargo run`
+            .or_else(|| day_of(&std::env::var("CARGO_PKG_NAME").ok()?))
+    }
+
+    /// Gets the directory with the day's inputs.
+    /// This is `dayN` next to the `tools` crate if it exists, or else the current directory.
+    pub fn day_dir(&self) -> PathBuf {
+        match self.day().map(day_dir) {
+            Some(dir) if dir.is_dir() => dir,
+            _ => PathBuf::from("."),
+        }
+    }
+
+    /// Reads the day's `aoc.toml` parameters, with `--param` values applied over them.
+    pub fn params(&self) -> Result<Params> {
+        let mut params = Params::load(&self.day_dir().join("aoc.toml"))?;
+        for (key, value) in &self.params {
+            params.set(key, value);
+        }
+        Ok(params)
+    }
+
+    /// Whether input is read from stdin instead of a file.
+    pub fn is_stdin(&self) -> bool {
+        self.input.as_deref() == Some(Path::new("-"))
+    }
+
+    /// Loads the input on first use, then shares it for the rest of the run.
+    pub fn buffer(&self) -> Result<&Buffer> {
+        if let Some(buffer) = self.buffer.get() {
+            return Ok(buffer);
+        }
+        let buffer = Buffer::load(&self.file()?)?;
+        Ok(self.buffer.get_or_init(|| buffer))
+    }
+
+    /// Gets the whole input, panicking with the error message on failure.
+    pub fn input(&self) -> &str {
+        self.try_input().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    pub fn lines(&self) -> Vec<&str> {
+        self.try_lines().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Gets the whole input.
+    pub fn try_input(&self) -> Result<&str> {
+        Ok(self.buffer()?.as_str())
+    }
+
+    pub fn try_lines(&self) -> Result<Vec<&str>> {
+        Ok(self.buffer()?.lines().collect())
+    }
+}
+
+/// Gets the repository root, the directory with the `tools` crate and all days.
+pub fn root_dir() -> PathBuf {
+    let tools = Path::new(env!("CARGO_MANIFEST_DIR"));
+    tools.parent().unwrap_or(tools).to_owned()
+}
+
+/// Gets the directory for a day's files, `dayN` in the repository root.
+pub fn day_dir(day: u8) -> PathBuf {
+    root_dir().join(format!("day{day}"))
+}
+
+/// Finds `input.txt` in `dir`, or else the only `input*.txt` there, like `input_1.txt`.
+fn find_input(dir: &Path) -> Result<PathBuf> {
+    let path = dir.join("input.txt");
+    if path.exists() {
+        return Ok(path);
+    }
+
+    match list_files(dir, "input")?.as_slice() {
+        [only] => Ok(only.clone()),
+        _ => Ok(path),
+    }
+}
+
+/// Finds an example input in `dir` by name or index.
+/// No name or `1` is `example.txt`, `2` or `example2` is `example2.txt`, and so on.
+fn find_example(dir: &Path, name: Option<&str>) -> Result<PathBuf> {
+    let name = name.unwrap_or("example");
+    let mut candidates = vec![dir.join(name), dir.join(format!("{name}.txt"))];
+    if name.parse::<usize>().is_ok() {
+        if name == "1" {
+            candidates.push(dir.join("example.txt"));
+        }
+        candidates.push(dir.join(format!("example{name}.txt")));
+    }
+
+    if let Some(path) = candidates.into_iter().find(|p| p.is_file()) {
+        return Ok(path);
+    }
+
+    let found: Vec<String> = list_files(dir, "example")?
+        .iter()
+        .filter_map(|p| Some(p.file_name()?.to_string_lossy().into_owned()))
+        .collect();
+    Err(Error::Usage(format!(
+        "no example '{name}' in {}, found: {}",
+        dir.display(),
+        if found.is_empty() { "none".to_owned() } else { found.join(", ") }
+    )))
+}
+
+/// Lists the `<prefix>*.txt` files in `dir`, except solution files, sorted by name.
+fn list_files(dir: &Path, prefix: &str) -> Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    for entry in std::fs::read_dir(dir).map_err(|e| Error::io(dir, e))? {
+        let path = entry.map_err(|e| Error::io(dir, e))?.path();
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        if name.starts_with(prefix) && name.ends_with(".txt") && !name.ends_with(".solution.txt") {
+            files.push(path);
+        }
+    }
+    files.sort();
+
+    Ok(files)
+}
+
+/// Parses a duration like `30s`, `2m`, `1h` or `500ms`. A plain number is seconds.
+fn parse_duration(arg: &str) -> std::result::Result<Duration, String> {
+    let invalid = || format!("invalid duration '{arg}', expected e.g. 30s, 2m or 500ms");
+
+    let split = arg.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(arg.len());
+    let (n, unit) = arg.split_at(split);
+    let n: f64 = n.parse().map_err(|_| invalid())?;
+    let secs = match unit {
+        "ms" => n / 1000.0,
+        "" | "s" => n,
+        "m" => n * 60.0,
+        "h" => n * 3600.0,
+        _ => return Err(invalid()),
+    };
+    Duration::try_from_secs_f64(secs).map_err(|_| invalid())
+}
+
+/// Selects which puzzle part(s) to solve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Part {
+    One,
+    Two,
+    Both,
+}
+
+impl Part {
+    /// Whether part `n` (1 or 2) is selected.
+    pub fn includes(&self, n: u8) -> bool {
+        match self {
+            Part::One => n == 1,
+            Part::Two => n == 2,
+            Part::Both => true,
+        }
+    }
+}
+
+impl FromStr for Part {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "1" => Ok(Part::One),
+            "2" => Ok(Part::Two),
+            "both" => Ok(Part::Both),
+            _ => Err(format!("invalid part '{s}', expected 1, 2 or both")),
+        }
+    }
+}
+
+/// How answers are printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// One human readable line per part.
+    Text,
+    /// One JSON object per line for each part.
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            _ => Err(format!("invalid format '{s}', expected text or json")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn example_names() {
+        let dir = std::env::temp_dir().join(format!("tools-examples-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["example.txt", "example2.txt", "example.solution.txt", "input_1.txt"] {
+            std::fs::write(dir.join(name), "").unwrap();
+        }
+
+        assert_eq!(find_example(&dir, None).unwrap(), dir.join("example.txt"));
+        assert_eq!(find_example(&dir, Some("1")).unwrap(), dir.join("example.txt"));
+        assert_eq!(find_example(&dir, Some("2")).unwrap(), dir.join("example2.txt"));
+        assert_eq!(find_example(&dir, Some("example2")).unwrap(), dir.join("example2.txt"));
+        let err = find_example(&dir, Some("3")).unwrap_err().to_string();
+        assert!(err.ends_with("found: example.txt, example2.txt"), "{err}");
+
+        assert_eq!(find_input(&dir).unwrap(), dir.join("input_1.txt"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 
-    pub fn lines(&self) -> Vec<String> {
-        self.input().lines().map(|l| l.to_owned()).collect()
+    #[test]
+    pub fn durations() {
+        assert_eq!(parse_duration("30s"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_duration("2m"), Ok(Duration::from_secs(120)));
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("1.5"), Ok(Duration::from_millis(1500)));
+        assert!(parse_duration("5 days").is_err());
+        assert!(parse_duration("s").is_err());
     }
 }
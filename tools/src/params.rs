@@ -0,0 +1,161 @@
+//! Named puzzle parameters, so solvers can be run with different constants.
+//!
+//! Values come from the `[params]` table of an optional `aoc.toml` in the day's directory,
+//! and `--param key=value` on the command line overrides them:
+//! ```toml
+//! [params]
+//! cycles = 1000
+//! ```
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use crate::{Error, Result};
+
+/// Raw parameter values by name.
+#[derive(Debug, Clone, Default)]
+pub struct Params {
+    values: HashMap<String, String>,
+}
+
+static PARAMS: OnceLock<Params> = OnceLock::new();
+
+impl Params {
+    /// Reads the `[params]` table from a config file, or no params if it doesn't exist.
+    pub fn load(path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text).map_err(|e| e.with_path(path)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(Error::io(path, e)),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let table: toml::Table = text
+            .parse()
+            .map_err(|e: toml::de::Error| Error::validation(e.message().to_owned()))?;
+
+        let mut values = HashMap::new();
+        if let Some(params) = table.get("params") {
+            let Some(params) = params.as_table() else {
+                return Err(Error::validation("`params` must be a table"));
+            };
+            for (key, value) in params {
+                let value = match value {
+                    toml::Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                values.insert(key.clone(), value);
+            }
+        }
+
+        Ok(Self { values })
+    }
+
+    /// Sets a parameter, replacing any value from the config file.
+    pub fn set(&mut self, key: &str, value: &str) {
+        self.values.insert(key.to_owned(), value.to_owned());
+    }
+
+    /// Gets a parameter parsed as `T`, or `None` if it isn't set.
+    pub fn get<T>(&self, key: &str) -> Result<Option<T>>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.values
+            .get(key)
+            .map(|value| {
+                value.parse().map_err(|e| {
+                    Error::Usage(format!("invalid value '{value}' for param '{key}': {e}"))
+                })
+            })
+            .transpose()
+    }
+}
+
+/// Makes `params` available through [`param`] and [`param_or`]. Only the first call has any effect.
+pub fn init(params: Params) {
+    let _ = PARAMS.set(params);
+}
+
+/// Gets a parameter parsed as `T`, or `None` if it isn't set.
+pub fn param<T>(key: &str) -> Result<Option<T>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    match PARAMS.get() {
+        Some(params) => params.get(key),
+        None => Ok(None),
+    }
+}
+
+/// Gets a parameter parsed as `T`, or `default` if it isn't set.
+pub fn param_or<T>(key: &str, default: T) -> Result<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    Ok(param(key)?.unwrap_or(default))
+}
+
+/// Gets all parameters as sorted `key=value` lines, to tell runs with different params apart.
+pub fn fingerprint() -> String {
+    let Some(params) = PARAMS.get() else {
+        return String::new();
+    };
+    let mut values: Vec<String> = params
+        .values
+        .iter()
+        .map(|(k, v)| format!("{k}={v}"))
+        .collect();
+    values.sort();
+    values.join("\n")
+}
+
+/// Parses a `key=value` command line argument.
+pub(crate) fn parse_arg(arg: &str) -> std::result::Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_owned(), value.trim().to_owned()))
+        }
+        _ => Err(format!("invalid param '{arg}', expected key=value")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn config_and_overrides() {
+        let mut params = Params::parse("[params]\ncycles = 1000\nname = \"abc\"\n").unwrap();
+        assert_eq!(params.get::<usize>("cycles").unwrap(), Some(1000));
+        assert_eq!(
+            params.get::<String>("name").unwrap(),
+            Some("abc".to_owned())
+        );
+        assert_eq!(params.get::<usize>("missing").unwrap(), None);
+        assert!(params.get::<usize>("name").is_err());
+
+        params.set("cycles", "5");
+        assert_eq!(params.get::<usize>("cycles").unwrap(), Some(5));
+
+        assert!(Params::parse("params = 1").is_err());
+        assert_eq!(Params::parse("").unwrap().values.len(), 0);
+    }
+
+    #[test]
+    pub fn args() {
+        assert_eq!(
+            parse_arg("unfold=3"),
+            Ok(("unfold".to_owned(), "3".to_owned()))
+        );
+        assert!(parse_arg("unfold").is_err());
+        assert!(parse_arg("=3").is_err());
+    }
+}
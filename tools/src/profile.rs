@@ -0,0 +1,192 @@
+//! Span timings written as a Chrome trace, for `--trace out.json`.
+//!
+//! The runner records `parse`, `part1` and `part2` spans, and solvers can add their own:
+//! ```
+//! let _span = tools::profile::span("lookup");
+//! ```
+//! The span ends when the guard is dropped. The file opens in `chrome://tracing`, Perfetto
+//! or Speedscope. Spans cost next to nothing when tracing is off.
+
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use serde::Serialize;
+
+use crate::{Error, Result};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static TRACE: Mutex<Option<Trace>> = Mutex::new(None);
+static NEXT_THREAD: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    /// Small id of this thread in the trace, assigned on its first span.
+    static THREAD: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Events recorded since tracing was enabled.
+struct Trace {
+    start: Instant,
+    events: Vec<Event>,
+    named: HashSet<u64>,
+}
+
+/// One entry of the trace event format.
+#[derive(Serialize)]
+struct Event {
+    name: String,
+    ph: &'static str,
+    /// Microseconds since tracing was enabled.
+    ts: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dur: Option<f64>,
+    pid: u32,
+    tid: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    args: Option<serde_json::Value>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct File<'a> {
+    trace_events: &'a [Event],
+    display_time_unit: &'static str,
+}
+
+/// Starts recording spans.
+pub fn enable() {
+    let mut trace = TRACE.lock().unwrap_or_else(|e| e.into_inner());
+    trace.get_or_insert_with(|| Trace {
+        start: Instant::now(),
+        events: vec![],
+        named: HashSet::new(),
+    });
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Times from now until the returned guard is dropped.
+pub fn span(name: &str) -> Span {
+    Span {
+        start: enabled().then(|| (name.to_owned(), Instant::now())),
+    }
+}
+
+/// Guard for a running span, see [`span`].
+#[must_use = "the span ends when this is dropped"]
+pub struct Span {
+    start: Option<(String, Instant)>,
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        if let Some((name, start)) = self.start.take() {
+            record(name, start, Instant::now());
+        }
+    }
+}
+
+fn record(name: String, start: Instant, end: Instant) {
+    let tid = THREAD.with(|id| {
+        if id.get() == 0 {
+            id.set(NEXT_THREAD.fetch_add(1, Ordering::Relaxed));
+        }
+        id.get()
+    });
+
+    let mut trace = TRACE.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(trace) = trace.as_mut() else {
+        return;
+    };
+
+    // Metadata event, so viewers show thread names instead of numbers
+    if trace.named.insert(tid) {
+        let thread = std::thread::current();
+        let name = thread.name().map(str::to_owned).unwrap_or(format!("thread {tid}"));
+        trace.events.push(Event {
+            name: "thread_name".to_owned(),
+            ph: "M",
+            ts: 0.0,
+            dur: None,
+            pid: 1,
+            tid,
+            args: Some(serde_json::json!({ "name": name })),
+        });
+    }
+
+    let micros = |at: Instant| at.duration_since(trace.start).as_secs_f64() * 1e6;
+    let event = Event {
+        name,
+        ph: "X",
+        ts: micros(start),
+        dur: Some(micros(end) - micros(start)),
+        pid: 1,
+        tid,
+        args: None,
+    };
+    trace.events.push(event);
+}
+
+/// Writes the spans recorded so far as a Chrome trace event file.
+pub fn write(path: &Path) -> Result<()> {
+    let trace = TRACE.lock().unwrap_or_else(|e| e.into_inner());
+    let events = trace.as_ref().map(|t| t.events.as_slice()).unwrap_or(&[]);
+    let file = File {
+        trace_events: events,
+        display_time_unit: "ns",
+    };
+
+    let json = serde_json::to_string(&file).expect("trace is serializable");
+    std::fs::write(path, json).map_err(|e| Error::io(path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn chrome_trace() {
+        {
+            let _off = span("before");
+        }
+        enable();
+        {
+            let _outer = span("outer");
+            let _inner = span("inner");
+        }
+        std::thread::Builder::new()
+            .name("worker".to_owned())
+            .spawn(|| drop(span("threaded")))
+            .unwrap()
+            .join()
+            .unwrap();
+
+        let path = std::env::temp_dir().join(format!("tools-trace-{}.json", std::process::id()));
+        write(&path).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let events = json["traceEvents"].as_array().unwrap();
+        let spans: Vec<&str> = events
+            .iter()
+            .filter(|e| e["ph"] == "X")
+            .map(|e| e["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(spans, vec!["inner", "outer", "threaded"]);
+
+        let outer = events.iter().find(|e| e["name"] == "outer").unwrap();
+        let inner = events.iter().find(|e| e["name"] == "inner").unwrap();
+        assert!(outer["ts"].as_f64() <= inner["ts"].as_f64());
+        assert!(outer["dur"].as_f64() >= inner["dur"].as_f64());
+        assert!(events
+            .iter()
+            .any(|e| e["ph"] == "M" && e["args"]["name"] == "worker"));
+    }
+}
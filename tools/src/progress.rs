@@ -0,0 +1,174 @@
+//! Throttled progress lines for long running loops, printed to stderr.
+//!
+//! Loops can also poll [`Progress::check`] to stop when the part is cancelled.
+//!
+//! Counters stay silent when stdout is not a terminal (e.g. piped into a script), or with `-q`.
+
+use std::io::IsTerminal;
+use std::time::{Duration, Instant};
+
+use crate::cancel::{self, CancelToken};
+use crate::log::{self, Level};
+use crate::Result;
+
+/// Counts steps of a loop, and every few seconds prints how fast it is going.
+/// With a known total, the line also has the percent done and an ETA.
+pub struct Progress {
+    name: String,
+    total: Option<usize>,
+    depth: usize,
+    interval: Duration,
+    visible: bool,
+    start: Instant,
+    count: usize,
+    last_time: Instant,
+    last_count: usize,
+    cancel: CancelToken,
+}
+
+impl Progress {
+    /// Starts a counter with no known total, which only reports its rate.
+    pub fn new(name: &str) -> Self {
+        let now = Instant::now();
+        Self {
+            name: name.to_owned(),
+            total: None,
+            depth: 0,
+            interval: Duration::from_secs(5),
+            visible: std::io::stdout().is_terminal() && log::enabled(Level::Warn),
+            start: now,
+            count: 0,
+            last_time: now,
+            last_count: 0,
+            cancel: cancel::token(),
+        }
+    }
+
+    /// Starts a counter that expects `total` steps.
+    pub fn with_total(name: &str, total: usize) -> Self {
+        Self {
+            total: Some(total),
+            ..Self::new(name)
+        }
+    }
+
+    /// Starts a counter for an inner loop, printed indented under this one.
+    pub fn child(&self, name: &str) -> Self {
+        Self {
+            name: format!("{} > {name}", self.name),
+            depth: self.depth + 1,
+            interval: self.interval,
+            visible: self.visible,
+            ..Self::new(name)
+        }
+    }
+
+    /// Sets the expected number of steps.
+    pub fn total(mut self, total: usize) -> Self {
+        self.total = Some(total);
+        self
+    }
+
+    /// Sets how often progress is printed.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Counts one step. Returns true when it's time to report, even if nothing is printed.
+    pub fn tick(&mut self) -> bool {
+        self.add(1)
+    }
+
+    /// Counts `n` steps. Returns true when it's time to report, even if nothing is printed.
+    pub fn add(&mut self, n: usize) -> bool {
+        self.count += n;
+        let now = Instant::now();
+        if now - self.last_time <= self.interval {
+            return false;
+        }
+
+        if self.visible {
+            eprintln!("{}", self.line(now));
+        }
+        self.last_count = self.count;
+        self.last_time = now;
+        true
+    }
+
+    /// Returns [`Error::Cancelled`](crate::Error::Cancelled) if the part being solved was
+    /// cancelled, e.g. by `--timeout`.
+    pub fn check(&self) -> Result<()> {
+        self.cancel.check()
+    }
+
+    /// Number of steps counted so far.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Formats the progress line, with the rate since the last report.
+    fn line(&self, now: Instant) -> String {
+        let indent = "  ".repeat(self.depth);
+        let elapsed = (now - self.last_time).as_secs_f64();
+        let rate = format_rate((self.count - self.last_count) as f64 / elapsed);
+
+        match self.total {
+            Some(total) if total > 0 => {
+                let done = self.count.min(total);
+                let percent = done as f64 * 100.0 / total as f64;
+                let per_step = (now - self.start).as_secs_f64() / done.max(1) as f64;
+                let eta = Duration::from_secs((per_step * (total - done) as f64).round() as u64);
+                format!(
+                    "{indent}{}: {done}/{total} ({percent:.1}%), {rate}/s, ETA {eta:?}",
+                    self.name
+                )
+            }
+            _ => format!("{indent}{}: {}, {rate}/s", self.name, self.count),
+        }
+    }
+}
+
+/// Formats a rate compactly, e.g. `12.3k`.
+fn format_rate(rate: f64) -> String {
+    match rate {
+        r if r >= 1e9 => format!("{:.1}G", r / 1e9),
+        r if r >= 1e6 => format!("{:.1}M", r / 1e6),
+        r if r >= 1e3 => format!("{:.1}k", r / 1e3),
+        r => format!("{r:.1}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn lines() {
+        let mut progress = Progress::with_total("points", 400);
+        progress.count = 100;
+        let start = progress.start;
+        progress.last_time = start;
+
+        let line = progress.line(start + Duration::from_secs(10));
+        assert_eq!(line, "points: 100/400 (25.0%), 10.0/s, ETA 30s");
+
+        let mut child = progress.child("cycles");
+        child.count = 25_000;
+        child.last_time = child.start;
+        let line = child.line(child.start + Duration::from_secs(2));
+        assert_eq!(line, "  points > cycles: 25000, 12.5k/s");
+    }
+
+    #[test]
+    pub fn throttled() {
+        let mut progress = Progress::new("loop").interval(Duration::from_secs(60));
+        assert!(!progress.tick());
+        assert!(!progress.add(10));
+        assert_eq!(progress.count(), 11);
+
+        let mut progress = progress.interval(Duration::ZERO);
+        std::thread::sleep(Duration::from_millis(1));
+        assert!(progress.tick());
+    }
+}
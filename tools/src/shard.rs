@@ -0,0 +1,291 @@
+//! Splitting a search across processes with `--shard i/n`, and merging their answers.
+//!
+//! A solver passes its work list through [`split`], which keeps every `n`th item for
+//! shard `i`. Each shard's answer is then a partial result, and `aoc merge` combines the
+//! `--format json` output of all shards into the final answer. Only the answers of parts
+//! that called [`split`] are tagged with their shard, the others are already whole.
+
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+use crate::{Error, Result};
+
+/// One of `count` shards, numbered from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Shard {
+    pub index: usize,
+    pub count: usize,
+}
+
+static SHARD: OnceLock<Shard> = OnceLock::new();
+
+/// Set when [`split`] keeps only this shard's items.
+static SPLIT: AtomicBool = AtomicBool::new(false);
+
+impl Shard {
+    /// Keeps this shard's items: the `index`th, then every `count`th after it.
+    pub fn split<T>(&self, items: impl IntoIterator<Item = T>) -> Vec<T> {
+        items
+            .into_iter()
+            .skip(self.index - 1)
+            .step_by(self.count)
+            .collect()
+    }
+}
+
+impl FromStr for Shard {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid = || format!("invalid shard '{s}', expected i/n like 1/4");
+        let (index, count) = s.split_once('/').ok_or_else(invalid)?;
+        let index: usize = index.trim().parse().map_err(|_| invalid())?;
+        let count: usize = count.trim().parse().map_err(|_| invalid())?;
+        if index == 0 || index > count {
+            return Err(format!("invalid shard '{s}', i must be from 1 to n"));
+        }
+        Ok(Shard { index, count })
+    }
+}
+
+impl Display for Shard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.index, self.count)
+    }
+}
+
+/// Makes `shard` the one used by [`split`]. Only the first call has any effect.
+pub fn init(shard: Shard) {
+    let _ = SHARD.set(shard);
+}
+
+/// Gets the shard this process works on, if the search is split.
+pub fn current() -> Option<Shard> {
+    SHARD.get().copied()
+}
+
+/// Keeps the items for this process's shard, or all of them when not sharded.
+pub fn split<T>(items: impl IntoIterator<Item = T>) -> Vec<T> {
+    match current() {
+        Some(shard) => {
+            SPLIT.store(true, Ordering::Relaxed);
+            shard.split(items)
+        }
+        None => items.into_iter().collect(),
+    }
+}
+
+/// Whether [`split`] kept only this shard's items since the last call, so the answer
+/// found since then is partial.
+pub fn take_split() -> bool {
+    SPLIT.swap(false, Ordering::Relaxed)
+}
+
+/// How partial answers of each shard are combined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Merge {
+    Sum,
+    Max,
+    Min,
+}
+
+impl Merge {
+    pub fn apply(&self, values: impl IntoIterator<Item = i128>) -> Option<i128> {
+        let mut values = values.into_iter();
+        match self {
+            Merge::Sum => values.next().map(|first| first + values.sum::<i128>()),
+            Merge::Max => values.max(),
+            Merge::Min => values.min(),
+        }
+    }
+}
+
+impl FromStr for Merge {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "sum" => Ok(Merge::Sum),
+            "max" => Ok(Merge::Max),
+            "min" => Ok(Merge::Min),
+            _ => Err(format!("invalid merge '{s}', expected sum, max or min")),
+        }
+    }
+}
+
+/// The fields of a `--format json` answer that merging needs.
+#[derive(Deserialize)]
+struct Record {
+    part: u8,
+    answer: Option<String>,
+    shard: Option<String>,
+}
+
+/// One shard's answer for a part.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Partial {
+    pub part: u8,
+    /// `None` if the part wasn't split, so every shard found the whole answer.
+    pub shard: Option<Shard>,
+    pub value: i128,
+}
+
+/// Reads the answers in a shard's `--format json` output.
+pub fn read_partials(text: &str) -> Result<Vec<Partial>> {
+    let mut partials = vec![];
+    for (i, line) in crate::input::lines(text).enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record: Record =
+            serde_json::from_str(line).map_err(|e| Error::parse(i + 1, e.to_string()))?;
+
+        let shard: Option<Shard> = record
+            .shard
+            .map(|shard| shard.parse())
+            .transpose()
+            .map_err(|e: String| Error::parse(i + 1, e))?;
+        let answer = record.answer.ok_or_else(|| {
+            Error::validation(format!("part {} is unsolved", record.part)).at_line(i + 1)
+        })?;
+        let value = answer
+            .parse()
+            .map_err(|_| Error::parse(i + 1, format!("answer '{answer}' is not a number")))?;
+
+        partials.push(Partial {
+            part: record.part,
+            shard,
+            value,
+        });
+    }
+
+    Ok(partials)
+}
+
+/// Combines the answers from every shard, by part.
+/// Fails if a part's shards are missing or repeated, or if a part that wasn't split has
+/// different answers.
+pub fn merge(merge: Merge, partials: &[Partial]) -> Result<Vec<(u8, i128)>> {
+    let mut parts: BTreeMap<u8, Vec<&Partial>> = BTreeMap::new();
+    for partial in partials {
+        parts.entry(partial.part).or_default().push(partial);
+    }
+
+    parts
+        .into_iter()
+        .map(|(part, partials)| {
+            let whole: Vec<i128> = partials
+                .iter()
+                .filter(|p| p.shard.is_none())
+                .map(|p| p.value)
+                .collect();
+            if whole.len() == partials.len() {
+                if whole.iter().any(|v| *v != whole[0]) {
+                    return Err(Error::Usage(format!(
+                        "part {part} wasn't split, but its answers differ"
+                    )));
+                }
+                return Ok((part, whole[0]));
+            }
+            if !whole.is_empty() {
+                return Err(Error::Usage(format!(
+                    "part {part} was only split in some shards"
+                )));
+            }
+
+            let mut shards: Vec<Shard> = partials.iter().filter_map(|p| p.shard).collect();
+            shards.sort();
+            let count = shards[0].count;
+            let expected = (1..=count).map(|index| Shard { index, count });
+            if !shards.iter().copied().eq(expected) {
+                let found: Vec<String> = shards.iter().map(|s| s.to_string()).collect();
+                return Err(Error::Usage(format!(
+                    "part {part} needs one answer from each of {count} shards, found {}",
+                    found.join(", ")
+                )));
+            }
+
+            let value = merge.apply(partials.iter().map(|p| p.value));
+            Ok((part, value.expect("every part has an answer")))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn splits() {
+        let shard: Shard = "2/3".parse().unwrap();
+        assert_eq!(shard.split(0..8), vec![1, 4, 7]);
+        assert_eq!(shard.to_string(), "2/3");
+
+        // Every item is in exactly one shard
+        let mut all: Vec<i32> = (1..=3)
+            .flat_map(|index| Shard { index, count: 3 }.split(0..8))
+            .collect();
+        all.sort();
+        assert_eq!(all, (0..8).collect::<Vec<_>>());
+
+        assert!("0/3".parse::<Shard>().is_err());
+        assert!("4/3".parse::<Shard>().is_err());
+        assert!("3".parse::<Shard>().is_err());
+    }
+
+    #[test]
+    pub fn merges() {
+        let text = r#"{"day":16,"part":2,"answer":"8331","shard":"2/2"}
+{"day":16,"part":2,"answer":"7900","shard":"1/2"}
+{"day":16,"part":1,"answer":"7860","shard":"1/2"}
+{"day":16,"part":1,"answer":"7860","shard":"2/2"}
+"#;
+        let partials = read_partials(text).unwrap();
+        assert_eq!(
+            merge(Merge::Max, &partials).unwrap(),
+            vec![(1, 7860), (2, 8331)]
+        );
+        assert_eq!(merge(Merge::Sum, &partials).unwrap()[1], (2, 16231));
+        assert_eq!(merge(Merge::Min, &partials).unwrap()[1], (2, 7900));
+
+        let missing = r#"{"part":2,"answer":"1","shard":"1/3"}
+{"part":2,"answer":"2","shard":"3/3"}"#;
+        let err = merge(Merge::Sum, &read_partials(missing).unwrap())
+            .unwrap_err()
+            .to_string();
+        assert!(err.ends_with("found 1/3, 3/3"), "{err}");
+
+        let unsolved = r#"{"part":2,"answer":null,"shard":"1/1"}"#;
+        assert!(read_partials(unsolved).is_err());
+    }
+
+    #[test]
+    pub fn merges_unsharded_parts() {
+        // Part 1 didn't call `split`, so each shard found the whole answer
+        let text = r#"{"day":16,"part":1,"answer":"46"}
+{"day":16,"part":2,"answer":"30","shard":"1/2"}
+{"day":16,"part":1,"answer":"46"}
+{"day":16,"part":2,"answer":"21","shard":"2/2"}
+"#;
+        let partials = read_partials(text).unwrap();
+        assert_eq!(partials[0].shard, None);
+        assert_eq!(
+            merge(Merge::Sum, &partials).unwrap(),
+            vec![(1, 46), (2, 51)]
+        );
+
+        let differ = r#"{"part":1,"answer":"46"}
+{"part":1,"answer":"45"}"#;
+        let err = merge(Merge::Sum, &read_partials(differ).unwrap()).unwrap_err();
+        assert!(err.to_string().contains("answers differ"), "{err}");
+
+        let mixed = r#"{"part":1,"answer":"46"}
+{"part":1,"answer":"45","shard":"2/2"}"#;
+        assert!(merge(Merge::Sum, &read_partials(mixed).unwrap()).is_err());
+    }
+}
@@ -0,0 +1,393 @@
+use std::fmt::Display;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::cache::AnswerCache;
+use crate::cancel::{self, CancelToken};
+use crate::checkpoint;
+use crate::client::{self, Client};
+use crate::memory::{self, Usage};
+use crate::profile;
+use crate::shard::{self, Shard};
+use crate::submit::{self, History, Outcome};
+use crate::{bench, check::Answers, opt::Part, params, warn, Error, Format, Opt, Result};
+
+/// A day's puzzle solution. The input is parsed once, then each part is solved from it.
+/// The solution can borrow from the input for `'a`, the lifetime of the options passed to [`run`].
+pub trait Solution<'a>: Sized {
+    type Part1: Display;
+    type Part2: Display;
+
+    /// Parses the puzzle input.
+    fn parse(input: &'a str) -> Result<Self>;
+
+    /// Solves part 1.
+    fn part1(&self) -> Result<Self::Part1>;
+
+    /// Solves part 2.
+    fn part2(&self) -> Result<Self::Part2>;
+}
+
+/// Loads the input for the command line options, then solves and prints the selected parts.
+/// The input stays in `opt`, so the solution can borrow from it:
+/// ```ignore
+/// fn main() -> tools::Result<()> {
+///     tools::run::<Day1>(&tools::Opt::load())
+/// }
+/// ```
+pub fn run<'a, S: Solution<'a>>(opt: &'a Opt) -> Result<()> {
+    if opt.trace.is_some() {
+        profile::enable();
+    }
+
+    let result = solve::<S>(opt);
+
+    // Written even if solving failed, to show where a slow part spent its time
+    if let Some(path) = &opt.trace {
+        profile::write(path)?;
+    }
+    result
+}
+
+fn solve<'a, S: Solution<'a>>(opt: &'a Opt) -> Result<()> {
+    let file = opt.file()?;
+    params::init(opt.params()?);
+    if let Some(shard) = opt.shard {
+        shard::init(shard);
+    }
+
+    if opt.bench == Some(0) {
+        return Err(Error::Usage("--bench needs at least one run".to_owned()));
+    }
+    if opt.bench.is_some() && opt.format == Format::Json {
+        return Err(Error::Usage("--bench only supports text output".to_owned()));
+    }
+
+    let expected = if opt.check {
+        if opt.is_stdin() {
+            return Err(Error::Usage(
+                "--check needs an input file, not stdin".to_owned(),
+            ));
+        }
+        Some(Answers::load(&Answers::file_for(&file))?)
+    } else {
+        None
+    };
+
+    let input = opt.try_input()?;
+    if let Some(runs) = opt.bench {
+        return benchmark::<S>(input, runs, opt.part).map_err(|e| e.with_path(&file));
+    }
+
+    // A shard's answers are partial, so they are cached and checkpointed apart from others
+    let key = match opt.shard {
+        Some(shard) => format!("{}\nshard={shard}", params::fingerprint()),
+        None => params::fingerprint(),
+    };
+    checkpoint::init(checkpoint::Context::new(
+        &opt.day_dir(),
+        input,
+        &key,
+        opt.resume,
+    ));
+
+    let output = Output {
+        format: opt.format,
+        day: opt.day(),
+        input: file.display().to_string(),
+        shard: opt.shard,
+    };
+
+    let mut cache = if opt.no_cache {
+        None
+    } else {
+        let path = AnswerCache::file_for(&opt.day_dir());
+        AnswerCache::load(&path, opt.day(), input, &key)?
+    };
+    let cached = |n: u8| {
+        let (value, sharded) = cache.as_ref()?.get(n)?;
+        Some(Answer::cached(value, sharded))
+    };
+
+    // Skip parsing too when every selected part is cached
+    let solution = if [1, 2]
+        .iter()
+        .any(|n| opt.part.includes(*n) && cached(*n).is_none())
+    {
+        let usage = Usage::start();
+        let solution = {
+            let _span = profile::span("parse");
+            S::parse(input).map_err(|e| e.with_path(&file).with_source(input))?
+        };
+        output.memory("Parse", &usage);
+        Some(solution)
+    } else {
+        None
+    };
+    let solution = || {
+        solution
+            .as_ref()
+            .expect("input is parsed when a part isn't cached")
+    };
+
+    let mut answers = vec![];
+    if opt.part.includes(1) {
+        let answer = match cached(1) {
+            Some(answer) => Some(answer),
+            None => {
+                let usage = Usage::start();
+                let answer = solve_part(1, opt.part, opt.timeout, || solution().part1())
+                    .map_err(|e| e.with_path(&file))?;
+                output.memory("Part 1", &usage);
+                answer
+            }
+        };
+        output.answer(1, answer.as_ref());
+        answers.push((1, answer));
+    }
+    if opt.part.includes(2) {
+        let answer = match cached(2) {
+            Some(answer) => Some(answer),
+            None => {
+                let usage = Usage::start();
+                let answer = solve_part(2, opt.part, opt.timeout, || solution().part2())
+                    .map_err(|e| e.with_path(&file))?;
+                output.memory("Part 2", &usage);
+                answer
+            }
+        };
+        output.answer(2, answer.as_ref());
+        answers.push((2, answer));
+    }
+
+    if let Some(cache) = &mut cache {
+        for (n, answer) in &answers {
+            let Some(answer) = answer else {
+                continue;
+            };
+            if answer.elapsed.is_some() {
+                // Failing to cache only makes the next run slower
+                if let Err(e) = cache.insert(*n, &answer.value, answer.sharded) {
+                    warn!("not caching part {n}: {e}");
+                }
+            }
+        }
+    }
+
+    if opt.submit {
+        submit_answers(opt, &output, &answers)?;
+    }
+
+    if let Some(expected) = expected {
+        let mut failed = 0;
+        for (n, answer) in answers {
+            if let Some(answer) = answer {
+                let verdict = expected.check(n, &answer.value);
+                failed += verdict.is_failure() as usize;
+                output.note(format_args!("Part {n}: {verdict}"));
+            }
+        }
+        if failed > 0 {
+            return Err(Error::CheckFailed(failed));
+        }
+    }
+
+    Ok(())
+}
+
+/// Submits solved parts in order, stopping at the first answer that isn't correct.
+fn submit_answers(opt: &Opt, output: &Output, answers: &[(u8, Option<Answer>)]) -> Result<()> {
+    let day = opt
+        .day()
+        .ok_or_else(|| Error::Usage("--submit needs to know the day, use --day".to_owned()))?;
+    let client = Client::from_env(&opt.base_url, client::DEFAULT_YEAR)?;
+    let mut history = History::load(&History::file_for(&opt.day_dir()))?;
+
+    for (n, answer) in answers {
+        let Some(answer) = answer else {
+            continue;
+        };
+        let outcome = submit::submit(&client, &mut history, day, *n, &answer.value)?;
+        output.note(format_args!("Part {n}: submitted, {outcome}"));
+        if outcome != Outcome::Correct {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// A part's answer, and how long it took to solve.
+struct Answer {
+    value: String,
+    /// `None` if the answer came from the cache.
+    elapsed: Option<Duration>,
+    /// Whether the part called [`shard::split`], so the answer is only this shard's.
+    sharded: bool,
+}
+
+impl Answer {
+    fn cached(value: &str, sharded: bool) -> Self {
+        Answer {
+            value: value.to_owned(),
+            elapsed: None,
+            sharded,
+        }
+    }
+}
+
+/// Solves part `n`, returning its answer and how long it took.
+/// When running both parts, an unsolved part gives `None` instead of stopping the run.
+/// With a `timeout`, the part is cancelled once it runs out of time.
+fn solve_part<T, F>(
+    n: u8,
+    selected: Part,
+    timeout: Option<Duration>,
+    solve: F,
+) -> Result<Option<Answer>>
+where
+    T: Display,
+    F: FnOnce() -> Result<T>,
+{
+    let token = CancelToken::new();
+    cancel::install(token.clone());
+
+    let span = profile::span(&format!("part{n}"));
+    shard::take_split();
+    let start = Instant::now();
+    let answer = match timeout {
+        Some(limit) => cancel::with_deadline(limit, &token, solve),
+        None => solve(),
+    };
+    let elapsed = start.elapsed();
+    let sharded = shard::take_split();
+    drop(span);
+
+    if let (Err(Error::Cancelled), Some(limit)) = (&answer, timeout) {
+        return Err(Error::TimedOut { part: n, limit });
+    }
+
+    match answer {
+        Ok(answer) => Ok(Some(Answer {
+            value: answer.to_string(),
+            elapsed: Some(elapsed),
+            sharded,
+        })),
+        Err(Error::Unsolved) if selected == Part::Both => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Prints answers in the selected format.
+struct Output {
+    format: Format,
+    day: Option<u8>,
+    input: String,
+    shard: Option<Shard>,
+}
+
+/// An answer printed by `--format json`.
+#[derive(Serialize)]
+struct Record<'a> {
+    day: Option<u8>,
+    part: u8,
+    /// `None` if the part is unsolved.
+    answer: Option<&'a str>,
+    input: &'a str,
+    /// `None` if the part is unsolved or cached.
+    elapsed_ns: Option<u128>,
+    cached: bool,
+    /// `i/n` when the part only searched one shard of the work.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    shard: Option<String>,
+}
+
+impl Output {
+    fn answer(&self, part: u8, answer: Option<&Answer>) {
+        match (self.format, answer) {
+            (
+                Format::Text,
+                Some(Answer {
+                    value,
+                    elapsed: Some(elapsed),
+                    ..
+                }),
+            ) => {
+                println!("Part {part}: {value} ({elapsed:?})")
+            }
+            (
+                Format::Text,
+                Some(Answer {
+                    value,
+                    elapsed: None,
+                    ..
+                }),
+            ) => {
+                println!("Part {part}: {value} (cached)")
+            }
+            (Format::Text, None) => println!("Part {part}: unsolved"),
+            (Format::Json, _) => {
+                let record = Record {
+                    day: self.day,
+                    part,
+                    answer: answer.map(|a| a.value.as_str()),
+                    input: &self.input,
+                    elapsed_ns: answer.and_then(|a| a.elapsed).map(|e| e.as_nanos()),
+                    cached: answer.is_some_and(|a| a.elapsed.is_none()),
+                    shard: self
+                        .shard
+                        .filter(|_| answer.is_some_and(|a| a.sharded))
+                        .map(|s| s.to_string()),
+                };
+                let json = serde_json::to_string(&record).expect("answer record is serializable");
+                println!("{json}");
+            }
+        }
+    }
+
+    /// Prints heap usage since `start`, if the counting allocator is installed.
+    fn memory(&self, label: &str, start: &Usage) {
+        if memory::enabled() {
+            self.note(format_args!("{label} memory: {}", Usage::since(start)));
+        }
+    }
+
+    /// Prints any other message, on stderr for JSON so stdout stays one object per part.
+    fn note(&self, message: std::fmt::Arguments) {
+        match self.format {
+            Format::Text => println!("{message}"),
+            Format::Json => eprintln!("{message}"),
+        }
+    }
+}
+
+/// Times parsing and each selected part separately, printing statistics for each.
+fn benchmark<'a, S: Solution<'a>>(input: &'a str, runs: usize, selected: Part) -> Result<()> {
+    let (solution, stats) =
+        bench::measure(runs, || S::parse(input)).map_err(|e| e.with_source(input))?;
+    println!("Parse: {stats}");
+
+    if selected.includes(1) {
+        bench_part(1, selected, runs, || solution.part1())?;
+    }
+    if selected.includes(2) {
+        bench_part(2, selected, runs, || solution.part2())?;
+    }
+
+    Ok(())
+}
+
+fn bench_part<T, F>(n: u8, selected: Part, runs: usize, solve: F) -> Result<()>
+where
+    T: Display,
+    F: FnMut() -> Result<T>,
+{
+    match bench::measure(runs, solve) {
+        Ok((answer, stats)) => println!("Part {n}: {answer} | {stats}"),
+        Err(Error::Unsolved) if selected == Part::Both => println!("Part {n}: unsolved"),
+        Err(e) => return Err(e),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,351 @@
+//! Answer submission, with a local history of every answer sent for a day.
+//!
+//! The history is kept in `submissions.jsonl` in the day's directory, one JSON object
+//! per submission. It is used to refuse answers that are already known to be wrong,
+//! and to wait out the cooldown the site asks for between submissions.
+
+use std::fmt::Display;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::client::Client;
+use crate::{Error, Result};
+
+/// The site's response to a submitted answer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    Correct,
+    TooHigh,
+    TooLow,
+    /// Wrong, without a hint.
+    Wrong,
+    /// Submitted too soon after the last answer, the answer was not checked.
+    Wait,
+    /// The part was already solved on the site.
+    AlreadySolved,
+    /// The page didn't say, e.g. a login or error page. Not recorded, so it can be resubmitted.
+    Unknown,
+}
+
+impl Outcome {
+    /// Whether the answer is known to be wrong.
+    pub fn is_wrong(&self) -> bool {
+        matches!(self, Outcome::TooHigh | Outcome::TooLow | Outcome::Wrong)
+    }
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Outcome::Correct => "correct",
+            Outcome::TooHigh => "too high",
+            Outcome::TooLow => "too low",
+            Outcome::Wrong => "wrong",
+            Outcome::Wait => "submitted too soon",
+            Outcome::AlreadySolved => "already solved",
+            Outcome::Unknown => "unknown, check the site",
+        };
+        write!(f, "{text}")
+    }
+}
+
+/// One submitted answer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Submission {
+    pub part: u8,
+    pub answer: String,
+    pub outcome: Outcome,
+    /// Unix time the answer was submitted.
+    pub time: u64,
+    /// Unix time until which the site won't accept another answer.
+    pub wait_until: Option<u64>,
+}
+
+/// The answers submitted for a day.
+pub struct History {
+    path: PathBuf,
+    submissions: Vec<Submission>,
+}
+
+impl History {
+    /// Gets the history file in a day's directory.
+    pub fn file_for(day_dir: &Path) -> PathBuf {
+        day_dir.join("submissions.jsonl")
+    }
+
+    /// Reads a history file, which is empty if it doesn't exist yet.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(Error::io(path, e)),
+        };
+
+        let mut submissions = vec![];
+        for (i, line) in crate::input::lines(&text).enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+            let submission = serde_json::from_str(line)
+                .map_err(|e| Error::parse(i + 1, e.to_string()).with_path(path))?;
+            submissions.push(submission);
+        }
+
+        Ok(Self {
+            path: path.to_owned(),
+            submissions,
+        })
+    }
+
+    pub fn submissions(&self) -> &[Submission] {
+        &self.submissions
+    }
+
+    /// Gets the correct answer for a part, if it was found.
+    pub fn correct(&self, part: u8) -> Option<&str> {
+        self.submissions
+            .iter()
+            .find(|s| s.part == part && s.outcome == Outcome::Correct)
+            .map(|s| s.answer.as_str())
+    }
+
+    /// Checks an answer against earlier wrong answers, including the too high/low bounds
+    /// for numbers. Returns the reason it must be wrong, if any.
+    pub fn known_wrong(&self, part: u8, answer: &str) -> Option<String> {
+        let value = answer.parse::<i128>().ok();
+
+        self.submissions
+            .iter()
+            .filter(|s| s.part == part && s.outcome.is_wrong())
+            .find_map(|s| {
+                let earlier = s.answer.parse::<i128>().ok();
+                match (&s.outcome, value, earlier) {
+                    _ if s.answer == answer => Some(format!("{answer} was {}", s.outcome)),
+                    (Outcome::TooHigh, Some(v), Some(e)) if v >= e => {
+                        Some(format!("{} was too high", s.answer))
+                    }
+                    (Outcome::TooLow, Some(v), Some(e)) if v <= e => {
+                        Some(format!("{} was too low", s.answer))
+                    }
+                    _ => None,
+                }
+            })
+    }
+
+    /// Gets how long until the site accepts another answer.
+    pub fn cooldown(&self, now: u64) -> Option<Duration> {
+        let until = self.submissions.iter().filter_map(|s| s.wait_until).max()?;
+        (until > now).then(|| Duration::from_secs(until - now))
+    }
+
+    /// Adds a submission and appends it to the history file.
+    pub fn record(&mut self, submission: Submission) -> Result<()> {
+        let line = serde_json::to_string(&submission).expect("submission is serializable");
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| Error::io(&self.path, e))?;
+        writeln!(file, "{line}").map_err(|e| Error::io(&self.path, e))?;
+
+        self.submissions.push(submission);
+        Ok(())
+    }
+}
+
+/// Submits an answer, unless the history shows it can't be right.
+/// Waits out any cooldown first, and records the response.
+pub fn submit(
+    client: &Client,
+    history: &mut History,
+    day: u8,
+    part: u8,
+    answer: &str,
+) -> Result<Outcome> {
+    if let Some(correct) = history.correct(part) {
+        if correct == answer {
+            return Ok(Outcome::Correct);
+        }
+        return Err(Error::Usage(format!(
+            "part {part} was already solved with {correct}, not submitting {answer}"
+        )));
+    }
+    if let Some(reason) = history.known_wrong(part, answer) {
+        return Err(Error::Usage(format!(
+            "not submitting {answer} for part {part}, {reason}"
+        )));
+    }
+
+    if let Some(wait) = history.cooldown(unix_now()) {
+        eprintln!("Waiting {wait:?} before submitting");
+        std::thread::sleep(wait);
+    }
+
+    let response = client.post_answer(day, part, answer)?;
+    let (outcome, wait) = parse_response(&response);
+    if outcome == Outcome::Unknown {
+        return Ok(outcome);
+    }
+    let time = unix_now();
+    history.record(Submission {
+        part,
+        answer: answer.to_owned(),
+        outcome: outcome.clone(),
+        time,
+        wait_until: wait.map(|w| time + w.as_secs()),
+    })?;
+
+    Ok(outcome)
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Reads the outcome from the site's response page, with how long to wait before the next answer.
+fn parse_response(html: &str) -> (Outcome, Option<Duration>) {
+    let outcome = if html.contains("That's the right answer") {
+        Outcome::Correct
+    } else if html.contains("You gave an answer too recently") {
+        Outcome::Wait
+    } else if html.contains("Did you already complete it") {
+        Outcome::AlreadySolved
+    } else if html.contains("your answer is too high") {
+        Outcome::TooHigh
+    } else if html.contains("your answer is too low") {
+        Outcome::TooLow
+    } else if html.contains("That's not the right answer") {
+        Outcome::Wrong
+    } else {
+        Outcome::Unknown
+    };
+
+    (outcome, parse_wait(html))
+}
+
+/// Finds the wait time in messages like `You have 1m 30s left to wait`
+/// or `please wait 5 minutes before trying again`.
+fn parse_wait(html: &str) -> Option<Duration> {
+    if let Some(end) = html.find(" left to wait") {
+        let start = html[..end].rfind("You have ")? + "You have ".len();
+        let mut secs = 0;
+        for word in html[start..end].split_whitespace() {
+            let (n, unit) = word.split_at(word.find(|c: char| !c.is_ascii_digit())?);
+            let n: u64 = n.parse().ok()?;
+            secs += match unit {
+                "h" => n * 3600,
+                "m" => n * 60,
+                _ => n,
+            };
+        }
+        return Some(Duration::from_secs(secs));
+    }
+
+    let start = html.find("wait ")? + "wait ".len();
+    let mut words = html[start..].split_whitespace();
+    let n = match words.next()? {
+        "one" => 1,
+        n => n.parse().ok()?,
+    };
+    match words.next()? {
+        unit if unit.starts_with("minute") => Some(Duration::from_secs(n * 60)),
+        unit if unit.starts_with("second") => Some(Duration::from_secs(n)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::tests::stub_server;
+
+    #[test]
+    pub fn responses() {
+        let (outcome, wait) = parse_response(
+            "<p>That's not the right answer; your answer is too high. Please wait one minute before trying again.</p>",
+        );
+        assert_eq!(outcome, Outcome::TooHigh);
+        assert_eq!(wait, Some(Duration::from_secs(60)));
+
+        let (outcome, wait) =
+            parse_response("<p>You gave an answer too recently. You have 1m 30s left to wait.</p>");
+        assert_eq!(outcome, Outcome::Wait);
+        assert_eq!(wait, Some(Duration::from_secs(90)));
+
+        assert_eq!(
+            parse_response("<p>That's the right answer!</p>"),
+            (Outcome::Correct, None)
+        );
+        assert_eq!(
+            parse_response("<p>That's not the right answer.</p>"),
+            (Outcome::Wrong, None)
+        );
+        assert_eq!(
+            parse_response("<h1>502 Bad Gateway</h1>"),
+            (Outcome::Unknown, None)
+        );
+    }
+
+    #[test]
+    pub fn history_rules() {
+        let dir = std::env::temp_dir().join(format!("tools-submit-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = History::file_for(&dir);
+        let _ = std::fs::remove_file(&path);
+
+        let (url, requests) = stub_server(vec![
+            (
+                200,
+                "<p>That's not the right answer; your answer is too high.</p>",
+            ),
+            (
+                200,
+                "<p>To play, please identify yourself via one of these services:</p>",
+            ),
+            (200, "<p>That's the right answer!</p>"),
+        ]);
+        let client = Client::new(&url, 2023, "abc123");
+        let mut history = History::load(&path).unwrap();
+
+        assert_eq!(
+            submit(&client, &mut history, 4, 1, "500").unwrap(),
+            Outcome::TooHigh
+        );
+        let request = requests.recv().unwrap();
+        assert!(request.starts_with("POST /2023/day/4/answer "), "{request}");
+        assert!(request.ends_with("level=1&answer=500"), "{request}");
+
+        // Known wrong answers are refused without asking the site
+        assert!(submit(&client, &mut history, 4, 1, "500").is_err());
+        assert!(submit(&client, &mut history, 4, 1, "600").is_err());
+
+        // An unrecognised page isn't recorded, so the answer can be submitted again
+        assert_eq!(
+            submit(&client, &mut history, 4, 1, "400").unwrap(),
+            Outcome::Unknown
+        );
+
+        assert_eq!(
+            submit(&client, &mut history, 4, 1, "400").unwrap(),
+            Outcome::Correct
+        );
+        assert_eq!(
+            submit(&client, &mut history, 4, 1, "400").unwrap(),
+            Outcome::Correct
+        );
+        assert!(submit(&client, &mut history, 4, 1, "300").is_err());
+
+        let history = History::load(&path).unwrap();
+        assert_eq!(history.submissions().len(), 2);
+        assert_eq!(history.correct(1), Some("400"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
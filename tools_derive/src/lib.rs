@@ -0,0 +1,227 @@
+//! Derive macros for `tools`, which re-exports them.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Fields, LitStr};
+
+/// Derives `FromStr` from the format of a line, with each `{field}` parsed by its own
+/// `FromStr`:
+/// ```ignore
+/// #[derive(AocParse)]
+/// #[aoc(format = "{address} = ({left}, {right})")]
+/// struct Node {
+///     address: Address,
+///     left: Address,
+///     right: Address,
+/// }
+/// ```
+/// A field ends where the text following it in the format starts, and `{{` and `}}` are
+/// literal braces. On enums, each variant has its own format and the first that matches is
+/// used. Errors are `tools::Error` diagnostics pointing at the part of the line that failed.
+#[proc_macro_derive(AocParse, attributes(aoc))]
+pub fn derive_aoc_parse(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let format = format_attr(&input.attrs, name)?;
+            constructor(quote!(Self), &data.fields, &format)?
+        }
+        Data::Enum(data) => {
+            if data.variants.is_empty() {
+                return Err(syn::Error::new_spanned(
+                    name,
+                    "AocParse needs at least one variant",
+                ));
+            }
+            let variants = data
+                .variants
+                .iter()
+                .map(|variant| {
+                    let ident = &variant.ident;
+                    let format = format_attr(&variant.attrs, ident)?;
+                    let constructor = constructor(quote!(Self::#ident), &variant.fields, &format)?;
+                    Ok(quote! {
+                        match (|| -> ::tools::Result<Self> { #constructor })() {
+                            Ok(value) => return Ok(value),
+                            Err(e) => errors.push(e),
+                        }
+                    })
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
+            quote! {
+                let mut errors = ::std::vec::Vec::new();
+                #(#variants)*
+                Err(::tools::format::furthest(errors))
+            }
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "AocParse is not supported on unions",
+            ))
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::std::str::FromStr for #name #ty_generics #where_clause {
+            type Err = ::tools::Error;
+
+            fn from_str(s: &str) -> ::tools::Result<Self> {
+                #body
+            }
+        }
+    })
+}
+
+/// Gets the `#[aoc(format = "...")]` of a struct or variant.
+fn format_attr(attrs: &[Attribute], owner: &syn::Ident) -> syn::Result<LitStr> {
+    let mut format = None;
+    for attr in attrs.iter().filter(|a| a.path().is_ident("aoc")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("format") {
+                format = Some(meta.value()?.parse::<LitStr>()?);
+                Ok(())
+            } else {
+                Err(meta.error("unknown aoc attribute, expected `format`"))
+            }
+        })?;
+    }
+    format.ok_or_else(|| syn::Error::new_spanned(owner, "missing #[aoc(format = \"...\")]"))
+}
+
+enum Segment {
+    Literal(String),
+    Field(String),
+}
+
+/// Splits a format into literal text and `{field}`s.
+fn segments(format: &LitStr) -> syn::Result<Vec<Segment>> {
+    let error = |message: &str| syn::Error::new_spanned(format, message);
+    let text = format.value();
+    let mut segments = vec![];
+    let mut literal = String::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                literal.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                literal.push('}');
+            }
+            '{' => {
+                let mut field = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => field.push(c),
+                        None => return Err(error("unclosed `{` in format")),
+                    }
+                }
+                let field = field.trim().to_owned();
+                if syn::parse_str::<syn::Ident>(&field).is_err() {
+                    return Err(error(&format!("`{{{field}}}` is not a field name")));
+                }
+                if !literal.is_empty() {
+                    segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                } else if let Some(Segment::Field(previous)) = segments.last() {
+                    return Err(error(&format!(
+                        "`{{{previous}}}` and `{{{field}}}` need text between them"
+                    )));
+                }
+                segments.push(Segment::Field(field));
+            }
+            '}' => return Err(error("unmatched `}` in format, use `}}` for a brace")),
+            c => literal.push(c),
+        }
+    }
+    if !literal.is_empty() {
+        segments.push(Segment::Literal(literal));
+    }
+
+    Ok(segments)
+}
+
+/// Parses `s` with `format` into `path`, a struct or variant with `fields`.
+fn constructor(path: TokenStream2, fields: &Fields, format: &LitStr) -> syn::Result<TokenStream2> {
+    let segments = segments(format)?;
+    let named: Vec<(String, &syn::Type)> = match fields {
+        Fields::Named(fields) => fields
+            .named
+            .iter()
+            .map(|f| (f.ident.as_ref().expect("named field").to_string(), &f.ty))
+            .collect(),
+        Fields::Unit => vec![],
+        Fields::Unnamed(_) => {
+            return Err(syn::Error::new_spanned(
+                fields,
+                "AocParse needs named fields to match the format",
+            ))
+        }
+    };
+
+    let mut used = vec![];
+    let mut steps = vec![];
+    for (i, segment) in segments.iter().enumerate() {
+        match segment {
+            Segment::Literal(literal) => steps.push(quote!(scanner.literal(#literal)?;)),
+            Segment::Field(field) => {
+                let Some((_, ty)) = named.iter().find(|(name, _)| name == field) else {
+                    return Err(syn::Error::new_spanned(
+                        format,
+                        format!("no field `{field}` to parse `{{{field}}}` into"),
+                    ));
+                };
+                if used.contains(field) {
+                    return Err(syn::Error::new_spanned(
+                        format,
+                        format!("`{{{field}}}` is in the format more than once"),
+                    ));
+                }
+                used.push(field.clone());
+
+                let until = match segments.get(i + 1) {
+                    Some(Segment::Literal(next)) => quote!(Some(#next)),
+                    _ => quote!(None),
+                };
+                let ident = format_ident!("{}", field);
+                steps.push(quote!(let #ident: #ty = scanner.field(#field, #until)?;));
+            }
+        }
+    }
+
+    if let Some((missing, _)) = named.iter().find(|(name, _)| !used.contains(name)) {
+        return Err(syn::Error::new_spanned(
+            format,
+            format!("field `{missing}` is not in the format"),
+        ));
+    }
+
+    let value = match fields {
+        Fields::Named(_) => {
+            let idents = named.iter().map(|(name, _)| format_ident!("{}", name));
+            quote!(#path { #(#idents),* })
+        }
+        _ => path,
+    };
+
+    Ok(quote! {
+        let mut scanner = ::tools::format::Scanner::new(s);
+        #(#steps)*
+        scanner.finish()?;
+        Ok(#value)
+    })
+}